@@ -0,0 +1,306 @@
+//! Implementation of the `#[builtin]` attribute macro.
+
+use crate::metadata_proc_macro::args::MacroArgs;
+use proc_macro::TokenStream;
+use proc_macro2::TokenTree;
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::spanned::Spanned;
+
+// The macro keeps the function, and generates the following items:
+//
+// * `__bash_builtin__options_<FN>`
+//
+//     A struct with a field for each parameter of the function, with the
+//     `BuiltinOptions` derive macro. Parameters with `#[opt]` are options,
+//     and the others are positional arguments.
+//
+// * `__bash_builtin__instance_<FN>`
+//
+//     A unit struct to implement the `Builtin` and `SharedBuiltin` traits.
+//     Its `call_shared` method parses the arguments and invokes the
+//     function. The struct has no state, so the builtin can be `reentrant`.
+//
+// Then, the `builtin_metadata!()` macro is invoked with the arguments of
+// the attribute, using the structs in the `create` and `options` keys.
+
+pub(crate) fn macro_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr = proc_macro2::TokenStream::from(attr);
+    let mut item_fn = syn::parse_macro_input!(item as syn::ItemFn);
+
+    match expand(attr, &mut item_fn) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => {
+            // Keep the function, without the attributes of the parameters,
+            // to avoid extra errors.
+            for input in item_fn.sig.inputs.iter_mut() {
+                if let syn::FnArg::Typed(input) = input {
+                    input
+                        .attrs
+                        .retain(|attr| !attr.path.is_ident("opt") && !attr.path.is_ident("arg"));
+                }
+            }
+
+            let mut tokens = e.into_compile_error();
+            item_fn.to_tokens(&mut tokens);
+            tokens.into()
+        }
+    }
+}
+
+fn expand(
+    attr: proc_macro2::TokenStream,
+    item_fn: &mut syn::ItemFn,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let args: MacroArgs = syn::parse2(attr.clone())?;
+
+    // Keys computed from the function.
+    for (key, span) in [
+        ("create", args.create.as_ref().map(|c| c.span())),
+        ("try_create", args.try_create.as_ref().map(|c| c.span())),
+        ("options", args.options.as_ref().map(|o| o.span())),
+    ] {
+        if let Some(span) = span {
+            return Err(syn::Error::new(
+                span,
+                format!("`{}` can't be used with #[builtin]", key),
+            ));
+        }
+    }
+
+    let sig = &item_fn.sig;
+    if let Some(token) = &sig.asyncness {
+        return Err(syn::Error::new(
+            token.span(),
+            "#[builtin] can't be used with async functions",
+        ));
+    }
+
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            sig.generics.span(),
+            "#[builtin] can't be used with generic functions",
+        ));
+    }
+
+    let fn_name = sig.ident.clone();
+    let fn_name_str = fn_name.to_string().trim_start_matches("r#").to_owned();
+    let options_type = format_ident!("__bash_builtin__options_{}", fn_name_str);
+    let instance_type = format_ident!("__bash_builtin__instance_{}", fn_name_str);
+
+    // Fields of the options struct, from the parameters.
+    let mut fields = Vec::new();
+    let mut field_names = Vec::new();
+    let mut has_arguments = false;
+
+    for input in item_fn.sig.inputs.iter_mut() {
+        let input = match input {
+            syn::FnArg::Typed(input) => input,
+            syn::FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new(
+                    receiver.span(),
+                    "#[builtin] can't be used with methods",
+                ))
+            }
+        };
+
+        let name = match &*input.pat {
+            syn::Pat::Ident(pat) if pat.by_ref.is_none() && pat.subpat.is_none() => {
+                pat.ident.clone()
+            }
+            pat => {
+                return Err(syn::Error::new(
+                    pat.span(),
+                    "expected an identifier for the parameter",
+                ))
+            }
+        };
+
+        if has_references(input.ty.to_token_stream()) {
+            return Err(syn::Error::new(
+                input.ty.span(),
+                "references are not supported; use an owned type, like `String` or `PathBuf`",
+            ));
+        }
+
+        let mut field_attrs = Vec::new();
+        let mut is_option = false;
+        for attr in std::mem::take(&mut input.attrs) {
+            if attr.path.is_ident("opt") {
+                is_option = true;
+                field_attrs.extend(opt_attr(attr)?);
+            } else if attr.path.is_ident("arg") {
+                field_attrs.push(attr.into_token_stream());
+            } else {
+                input.attrs.push(attr);
+            }
+        }
+
+        if !is_option && field_attrs.is_empty() {
+            field_attrs.push(quote! { #[arg] });
+        }
+
+        has_arguments |= !is_option;
+
+        let ty = &input.ty;
+        fields.push(quote! {
+            #(#field_attrs)*
+            #name: #ty
+        });
+
+        field_names.push(name);
+    }
+
+    // Call to the function, as the body of `Builtin::call`.
+    let call = match &item_fn.sig.output {
+        syn::ReturnType::Default => quote! {
+            #fn_name(#(__bash_builtin__opts.#field_names),*);
+            Ok(())
+        },
+
+        syn::ReturnType::Type(_, ty) => quote_spanned! {ty.span()=>
+            #fn_name(#(__bash_builtin__opts.#field_names),*)
+        },
+    };
+
+    // If there are no positional arguments, the parser does not consume
+    // them, so they are rejected after the options.
+    let finished = if has_arguments {
+        None
+    } else {
+        Some(quote! { args.finished()?; })
+    };
+
+    // Arguments for `builtin_metadata!()`.
+    let mut metadata_args = Vec::new();
+
+    if args.name.is_none() && args.names.is_none() {
+        metadata_args.push(quote! { name = #fn_name_str });
+    }
+
+    if args.long_doc.is_none() {
+        if let Some(long_doc) = doc_text(&item_fn.attrs) {
+            let long_doc = syn::LitStr::new(&long_doc, item_fn.sig.ident.span());
+            metadata_args.push(quote! { long_doc = #long_doc });
+        }
+    }
+
+    let mut attr: Vec<_> = attr.into_iter().collect();
+    if matches!(attr.last(), Some(TokenTree::Punct(p)) if p.as_char() == ',') {
+        attr.pop();
+    }
+
+    if !attr.is_empty() {
+        metadata_args.push(attr.into_iter().collect());
+    }
+
+    metadata_args.push(quote! { create = #instance_type::default });
+    metadata_args.push(quote! { options = #options_type });
+
+    Ok(quote! {
+        #item_fn
+
+        #[allow(non_camel_case_types)]
+        #[derive(::bash_builtins::BuiltinOptions)]
+        struct #options_type {
+            #(#fields,)*
+        }
+
+        #[allow(non_camel_case_types)]
+        #[derive(Default)]
+        struct #instance_type;
+
+        impl ::bash_builtins::Builtin for #instance_type {
+            fn call(
+                &mut self,
+                args: &mut ::bash_builtins::Args,
+            ) -> ::bash_builtins::Result<()> {
+                ::bash_builtins::SharedBuiltin::call_shared(self, args)
+            }
+        }
+
+        impl ::bash_builtins::SharedBuiltin for #instance_type {
+            fn call_shared(
+                &self,
+                args: &mut ::bash_builtins::Args,
+            ) -> ::bash_builtins::Result<()> {
+                let __bash_builtin__opts: #options_type = args.parse()?;
+                #finished
+                #call
+            }
+        }
+
+        ::bash_builtins::builtin_metadata!(#(#metadata_args),*);
+    })
+}
+
+/// Convert a `#[opt(…)]` attribute of a parameter to the attributes for
+/// the field.
+///
+/// The `help = "…"` key is removed, and its value is added as a doc
+/// comment, since doc comments are not allowed in function parameters.
+fn opt_attr(attr: syn::Attribute) -> Result<Vec<proc_macro2::TokenStream>, syn::Error> {
+    let mut list = match attr.parse_meta()? {
+        syn::Meta::List(list) => list,
+        _ => return Ok(vec![attr.into_token_stream()]),
+    };
+
+    let mut attrs = Vec::new();
+    let mut nested = syn::punctuated::Punctuated::<syn::NestedMeta, syn::Token![,]>::new();
+
+    for item in std::mem::take(&mut list.nested) {
+        match item {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(value)) if value.path.is_ident("help") => {
+                match value.lit {
+                    syn::Lit::Str(help) => attrs.push(quote! { #[doc = #help] }),
+                    lit => {
+                        return Err(syn::Error::new(
+                            lit.span(),
+                            "#[opt(help = \"…\")] requires a string",
+                        ))
+                    }
+                }
+            }
+
+            item => nested.push(item),
+        }
+    }
+
+    list.nested = nested;
+    attrs.push(quote! { #[#list] });
+
+    Ok(attrs)
+}
+
+/// Text of the doc comments (`/// …`) of the function, to be used as
+/// `long_doc`.
+///
+/// Unlike the doc comments of the options, lines are joined with a new
+/// line, so the text can contain multiple paragraphs.
+fn doc_text(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines: Vec<_> = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(lit),
+                ..
+            })) => Some(lit.value()),
+            _ => None,
+        })
+        .collect();
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+/// Returns `true` if the type contains a reference, or a lifetime.
+fn has_references(tokens: proc_macro2::TokenStream) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Punct(p) => p.as_char() == '&' || p.as_char() == '\'',
+        TokenTree::Group(g) => has_references(g.stream()),
+        _ => false,
+    })
+}
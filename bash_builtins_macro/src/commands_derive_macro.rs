@@ -0,0 +1,196 @@
+//! Implementation of the `BuiltinCommands` derive macro.
+
+use crate::options_derive_macro::{doc_comments, generics_ext};
+use proc_macro::TokenStream;
+use quote::quote;
+use std::collections::HashSet;
+use syn::spanned::Spanned;
+
+/// A subcommand, from an enum variant.
+struct Command {
+    name: String,
+    variant: syn::Ident,
+
+    /// Type to parse the arguments of the subcommand. `None` for unit
+    /// variants.
+    parser: Option<syn::Type>,
+
+    doc: String,
+}
+
+pub(crate) fn macro_impl(args: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(args as syn::DeriveInput);
+
+    let tokens = match &input.data {
+        syn::Data::Enum(data) => enum_impl(&input, data),
+        _ => Err(syn::Error::new(input.span(), "expected an enum")),
+    };
+
+    match tokens {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+/// Generate the `FromArgs` and `OptionsDoc` implementations.
+///
+/// The first argument selects the variant, and the parser of that variant
+/// gets the remaining arguments.
+fn enum_impl(
+    input: &syn::DeriveInput,
+    data: &syn::DataEnum,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let mut found_names = HashSet::new();
+
+    let commands = data
+        .variants
+        .iter()
+        .map(|variant| parse_variant(variant, &mut found_names))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let match_commands = commands.iter().enumerate().map(|(index, command)| {
+        let variant = &command.variant;
+
+        match &command.parser {
+            // Variants without fields don't accept any argument.
+            None => quote! {
+                #index => {
+                    __bash_builtin__args.finished()?;
+                    Ok(Self::#variant)
+                }
+            },
+
+            Some(parser) => quote! {
+                #index => <#parser as ::bash_builtins::FromArgs<'__bash_builtin__cstr>>::from_args(
+                    __bash_builtin__args
+                ).map(Self::#variant)
+            },
+        }
+    });
+
+    let commands_doc = commands.iter().map(|command| {
+        let name = &command.name;
+        let doc = &command.doc;
+
+        let (options, arguments) = match &command.parser {
+            None => (quote! { &[] }, quote! { "" }),
+
+            Some(parser) => (
+                quote! { <#parser as ::bash_builtins::doc::OptionsDoc>::OPTIONS },
+                quote! { <#parser as ::bash_builtins::doc::OptionsDoc>::ARGUMENTS },
+            ),
+        };
+
+        quote! {
+            ::bash_builtins::doc::CommandDoc {
+                name: #name,
+                options: #options,
+                arguments: #arguments,
+                doc: #doc,
+            }
+        }
+    });
+
+    let generics_ext = generics_ext(&input.generics);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let (impl_generics_ext, _, where_clause_ext) = generics_ext.split_for_impl();
+
+    let type_name = &input.ident;
+
+    Ok(quote! {
+        impl #impl_generics_ext ::bash_builtins::FromArgs<'__bash_builtin__cstr> for #type_name #ty_generics
+        #where_clause_ext
+        {
+            fn from_args(
+                __bash_builtin__args: &'__bash_builtin__cstr mut ::bash_builtins::Args,
+            ) -> ::bash_builtins::Result<Self> {
+                let __bash_builtin__commands =
+                    <Self as ::bash_builtins::doc::OptionsDoc>::COMMANDS;
+
+                match __bash_builtin__args.subcommand(__bash_builtin__commands)? {
+                    #(#match_commands,)*
+                    _ => unreachable!(),
+                }
+            }
+        }
+
+        impl #impl_generics ::bash_builtins::doc::OptionsDoc for #type_name #ty_generics
+        #where_clause
+        {
+            const OPTIONS: &'static [::bash_builtins::doc::OptionDoc] = &[];
+
+            const COMMANDS: &'static [::bash_builtins::doc::CommandDoc] = &[ #(#commands_doc,)* ];
+        }
+    })
+}
+
+fn parse_variant(
+    variant: &syn::Variant,
+    found_names: &mut HashSet<String>,
+) -> Result<Command, syn::Error> {
+    macro_rules! err {
+        ($err:expr) => {
+            return Err(syn::Error::new(variant.span(), $err))
+        };
+    }
+
+    let name = match variant
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("command"))
+    {
+        None => kebab_case(&variant.ident.to_string()),
+
+        Some(attr) => match attr.parse_meta()? {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(lit),
+                ..
+            }) => lit.value(),
+
+            _ => err!("#[command = \"…\"] requires a string"),
+        },
+    };
+
+    if name.is_empty() || name.starts_with('-') || name.contains(char::is_whitespace) {
+        err!("invalid command name");
+    }
+
+    if !found_names.insert(name.clone()) {
+        err!(format!("duplicated command '{}'", name));
+    }
+
+    let parser = match &variant.fields {
+        syn::Fields::Unit => None,
+
+        syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            fields.unnamed.first().map(|field| field.ty.clone())
+        }
+
+        _ => err!("commands must have a single unnamed field, or no fields"),
+    };
+
+    Ok(Command {
+        name,
+        variant: variant.ident.clone(),
+        parser,
+        doc: doc_comments(&variant.attrs),
+    })
+}
+
+/// Default name for a command, from the name of its variant.
+///
+/// Words are converted to lowercase and separated by `-`, so `ListAll`
+/// becomes `list-all`.
+pub(crate) fn kebab_case(variant: &str) -> String {
+    let mut name = String::with_capacity(variant.len() + 4);
+
+    for ch in variant.trim_start_matches("r#").chars() {
+        if ch.is_uppercase() && !name.is_empty() {
+            name.push('-');
+        }
+
+        name.extend(ch.to_lowercase());
+    }
+
+    name
+}
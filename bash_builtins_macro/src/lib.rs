@@ -1,7 +1,10 @@
 //! Macros for the `bash_builtins` crate.
 
+mod builtin_attr_macro;
+mod commands_derive_macro;
 mod metadata_proc_macro;
 mod options_derive_macro;
+mod word_pointer_derive_macro;
 
 use proc_macro::TokenStream;
 
@@ -11,8 +14,26 @@ pub fn builtin_metadata(args: TokenStream) -> TokenStream {
     metadata_proc_macro::macro_impl(args)
 }
 
-#[proc_macro_derive(BuiltinOptions, attributes(opt))]
+#[proc_macro_attribute]
+#[doc = include_str!("doc/builtin_attr_macro.md")]
+pub fn builtin(attr: TokenStream, item: TokenStream) -> TokenStream {
+    builtin_attr_macro::macro_impl(attr, item)
+}
+
+#[proc_macro_derive(BuiltinOptions, attributes(opt, arg))]
 #[doc = include_str!("doc/options_derive_macro.md")]
 pub fn derive_options(args: TokenStream) -> TokenStream {
     options_derive_macro::macro_impl(args)
 }
+
+#[proc_macro_derive(BuiltinCommands, attributes(command))]
+#[doc = include_str!("doc/commands_derive_macro.md")]
+pub fn derive_commands(args: TokenStream) -> TokenStream {
+    commands_derive_macro::macro_impl(args)
+}
+
+#[proc_macro_derive(FromWordPointer, attributes(choice))]
+#[doc = include_str!("doc/word_pointer_derive_macro.md")]
+pub fn derive_word_pointer(args: TokenStream) -> TokenStream {
+    word_pointer_derive_macro::macro_impl(args)
+}
@@ -1,58 +1,122 @@
 //! Implementation of the `builtin_metadata!()` macro.
 
 use proc_macro::TokenStream;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 
-// The macro generates the following items:
+// The macro generates the following items. `<NAME>` is the first name of the
+// builtin, and `<EACH>` is repeated for every name given in `names`.
 //
-// * `<NAME>_struct`
+// * `<EACH>_struct`
 //
 //     A global variable to initialize the fields required by the
 //     [`struct builtin`].
 //
 //     This symbol is loaded by bash to get the builtin metadata.
 //
-// * `__bash_builtin__state_<NAME>`
+// * `__bash_builtin__state_<NAME>` and `__bash_builtin__state_data_<NAME>`
 //
-//     A reference to a global variable used to store the builtin instance.
+//     A reference to a global variable used to store the builtin instance,
+//     and the variable itself. It is shared by all names.
 //
 // * `__bash_builtin__state_init_<NAME>`
 //
 //     A global variable to track if the state has been initialized.
 //
-// * `<NAME>_builtin_load`
+// * `__bash_builtin__loaded_<NAME>`
+//
+//     A global variable to track which names are loaded. The state is
+//     dropped when the last name is unloaded.
+//
+// * `__bash_builtin__names_<NAME>`
+//
+//     A global variable with the list of names, to invoke `on_load` when the
+//     instance is created in the first call.
+//
+// * `__bash_builtin__shell_pid_<NAME>` and `__bash_builtin__pid_<NAME>`
+//
+//     Global variables with the process id where the instance was created,
+//     and the process id of the last call. They are used to detect subshells.
+//
+// * `__bash_builtin__depth_<NAME>`
+//
+//     A global variable with the number of calls in progress, to detect
+//     recursive invocations.
+//
+// * `__bash_builtin__interrupted_<NAME>`
+//
+//     The unwind-protect function to reset the state left by calls
+//     interrupted by bash.
+//
+// * `__bash_builtin__exit_hook_<NAME>` and `__bash_builtin__exit_<NAME>`
+//
+//     The function to invoke `on_shell_exit`, and a global variable to
+//     register it only once.
+//
+// * `__bash_builtin__doc_<EACH>`
+//
+//     Only if the `options` argument is present. A global variable to keep
+//     the documentation generated from the options.
+//
+// * `<EACH>_builtin_load`
 //
 //     A function invoked by bash to initialize the builtin.
 //
-// * `<NAME>_builtin_unload`
+// * `<EACH>_builtin_unload`
 //
 //     A function invoked by bash when the builtin is removed
-//     (`enable -d <NAME>` in the prompt).
+//     (`enable -d <EACH>` in the prompt).
 //
-// * `__bash_builtin__func_<NAME>`
+// * `__bash_builtin__func_<EACH>`
 //
 //     The function invoked by bash when the builtin is typed in the prompt.
 //
+// * `__bash_builtin__create_<NAME>`
+//
+//     Only if `lazy` is true, or `on_panic` is `"reset"`. The function to
+//     create the instance when the builtin is invoked.
+//
+// * `__bash_builtin__call_<NAME>`
+//
+//     The function to invoke the `Builtin` instance, shared by all names.
+//
 // [`struct builtin`]: https://git.savannah.gnu.org/cgit/bash.git/tree/builtins.h?h=bash-5.1#n52
 
 pub(crate) fn macro_impl(args: TokenStream) -> TokenStream {
     let args = syn::parse_macro_input!(args as args::MacroArgs);
 
-    let name = args
-        .name
-        .as_ref()
-        .expect("`name` argument is required")
-        .value();
+    let names = match builtin_names(&args) {
+        Ok(names) => names,
+        Err(e) => return e.into_compile_error().into(),
+    };
 
-    // Symbols expected by bash.
-    let struct_bash_symbol = format_ident!("{}_struct", name);
-    let load_bash_symbol = format_ident!("{}_builtin_load", name);
-    let unload_bash_symbol = format_ident!("{}_builtin_unload", name);
+    // Behaviour after a panic.
+    let on_panic = match OnPanic::from_args(&args) {
+        Ok(on_panic) => on_panic,
+        Err(e) => return e.into_compile_error().into(),
+    };
 
-    // Internal items.
-    let global_state = format_ident!("__bash_builtin__state_{}", name);
-    let global_state_init = format_ident!("__bash_builtin__state_init_{}", name);
-    let builtin_func = format_ident!("__bash_builtin__func_{}", name);
+    // Flags for bash.
+    let flags = match builtin_flags(&args) {
+        Ok(flags) => flags,
+        Err(e) => return e.into_compile_error().into(),
+    };
+
+    // Internal items, shared by all names.
+    let first_name = &names[0];
+    let global_state = format_ident!("__bash_builtin__state_{}", first_name);
+    let global_state_init = format_ident!("__bash_builtin__state_init_{}", first_name);
+    let global_loaded = format_ident!("__bash_builtin__loaded_{}", first_name);
+    let call_func = format_ident!("__bash_builtin__call_{}", first_name);
+    let global_names = format_ident!("__bash_builtin__names_{}", first_name);
+    let global_shell_pid = format_ident!("__bash_builtin__shell_pid_{}", first_name);
+    let global_pid = format_ident!("__bash_builtin__pid_{}", first_name);
+    let global_exit_hook = format_ident!("__bash_builtin__exit_hook_{}", first_name);
+    let exit_func = format_ident!("__bash_builtin__exit_{}", first_name);
+    let create_func = format_ident!("__bash_builtin__create_{}", first_name);
+    let global_depth = format_ident!("__bash_builtin__depth_{}", first_name);
+    let global_state_data = format_ident!("__bash_builtin__state_data_{}", first_name);
+    let interrupted_func = format_ident!("__bash_builtin__interrupted_{}", first_name);
+    let names_count = names.len();
 
     let empty_str = quote! { [0].as_ptr() };
 
@@ -76,168 +140,896 @@ pub(crate) fn macro_impl(args: TokenStream) -> TokenStream {
         }
     };
 
-    // Path to the constructor.
-    let constructor = match (args.create.as_ref(), args.try_create.as_ref()) {
-        (Some(path), None) => quote! { Box::new(#path()) },
+    // If `reentrant` is true, the builtin can be invoked while another call is
+    // running. The instance is stored as a `dyn SharedBuiltin`, so the type
+    // must implement that trait.
+    let reentrant = args.reentrant.as_ref().is_some_and(|r| r.value);
+
+    let dyn_builtin = if reentrant {
+        quote! { dyn ::bash_builtins::SharedBuiltin }
+    } else {
+        quote! { dyn ::bash_builtins::Builtin }
+    };
+
+    // Expression to create the instance, as a `Box<#dyn_builtin>`.
+    //
+    // `on_error` is executed if `try_create` returns an error, in the
+    // variable `e`. `on_warning` is executed if the function returns an
+    // instance with a warning, in the variable `w`.
+    let constructor = |on_error: proc_macro2::TokenStream, on_warning: proc_macro2::TokenStream| {
+        let value = match (args.create.as_ref(), args.try_create.as_ref()) {
+            (Some(path), None) => quote! { #path() },
+
+            (None, Some(path)) => quote! {
+                match #path() {
+                    Ok(s) => s,
+                    Err(e) => { #on_error },
+                }
+            },
+            _ => panic!("one of `create` or `try_create` is required"),
+        };
 
-        (None, Some(path)) => quote! {
-            match #path() {
-                Ok(s) => Box::new(s),
+        quote! {
+            {
+                let (state, warning) =
+                    ::bash_builtins::IntoBuiltin::<#dyn_builtin>::into_builtin(#value);
+                if let Some(w) = warning {
+                    #on_warning
+                }
 
-                Err(e) => {
-                    use ::std::io::{stderr, Write};
-                    let _ = writeln!(stderr(), concat!(#name, ": error: {}"), e);
-                    return 0;
-                },
+                state
             }
-        },
-        _ => panic!("one of `create` or `try_create` is required"),
+        }
+    };
+
+    // Statements executed after creating the instance. The process id is
+    // used to detect subshells, and to invoke `on_shell_exit` only in the
+    // main shell.
+    let created = quote! {
+        let pid = ::std::process::id();
+        #global_shell_pid.store(pid, SeqCst);
+        #global_pid.store(pid, SeqCst);
+        #global_exit_hook.call_once(|| ::bash_builtins::ffi::at_exit(#exit_func));
+    };
+
+    // If `lazy` is true, the instance is created in the first call.
+    let lazy = args.lazy.as_ref().is_some_and(|lazy| lazy.value);
+
+    let load_constructor = |index: usize, name: &str| {
+        if lazy {
+            return None;
+        }
+
+        let constructor = constructor(
+            quote! {
+                ::bash_builtins::log::error(format!("{}: {}", #name, e));
+                return 0;
+            },
+            quote! {
+                ::bash_builtins::log::warning(format!("{}: {}", #name, w));
+            },
+        );
+
+        Some(quote! {
+            // The instance is shared with the other names, so it is
+            // created only if none of them is loaded.
+            let shared = #global_loaded
+                .iter()
+                .enumerate()
+                .any(|(index, loaded)| index != #index && loaded.load(SeqCst));
+
+            if !shared || lock.is_none() {
+                let state = #constructor;
+                *lock = Some(state);
+                #created
+            }
+        })
+    };
+
+    // Function to create the instance when the builtin is invoked, either
+    // because `lazy` is true, or to replace it after a panic.
+    let create_func_item = if lazy || on_panic == OnPanic::Reset {
+        let constructor = constructor(
+            quote! {
+                ::bash_builtins::error!("{}", e);
+                return None;
+            },
+            quote! {
+                ::bash_builtins::warning!("{}", w);
+            },
+        );
+
+        Some(quote! {
+            fn #create_func() -> ::std::option::Option<::std::boxed::Box<#dyn_builtin>> {
+                use ::std::sync::atomic::Ordering::SeqCst;
+
+                let mut state = #constructor;
+                #created
+
+                for (name, loaded) in #global_names.iter().zip(&#global_loaded) {
+                    if loaded.load(SeqCst) {
+                        state.on_load(name);
+                    }
+                }
+
+                Some(state)
+            }
+        })
+    } else {
+        None
+    };
+
+    let call_constructor = if lazy {
+        Some(quote! {
+            if lock.is_none() {
+                match #create_func() {
+                    Some(state) => *lock = Some(state),
+                    None => return ::bash_builtins::ffi::exit::EXECUTION_FAILURE,
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    // Statements executed after a panic in the call function.
+    let panic_recovery = match on_panic {
+        OnPanic::Keep => None,
+
+        // The lock is not held during the call in reentrant mode, so the
+        // mutex is not poisoned. Instead, the instance is removed and leaked,
+        // since other calls can still use it.
+        OnPanic::Poison if reentrant => Some(quote! {
+            if let Ok(mut lock) = #global_state().lock() {
+                ::std::mem::forget(lock.take());
+            }
+        }),
+
+        OnPanic::Poison => None,
+
+        // The poisoned mutex, and the instance inside it, are leaked, since
+        // we don't trust the state of the instance.
+        OnPanic::Reset => {
+            let replace = if lazy {
+                None
+            } else {
+                Some(quote! {
+                    if let Ok(mut lock) = #global_state().lock() {
+                        *lock = #create_func();
+                    }
+                })
+            };
+
+            Some(quote! {
+                #global_state_init.store(false, SeqCst);
+                #replace
+            })
+        }
+    };
+
+    // If the mutex is poisoned, `keep` uses it anyway.
+    let poisoned_lock = if on_panic == OnPanic::Keep {
+        quote! { Err(poison) => poison.into_inner(), }
+    } else {
+        quote! {
+            _ => {
+                ::bash_builtins::log::error("invalid internal state");
+                return RETVAL_ERROR;
+            }
+        }
     };
 
     let struct_type = quote! { ::bash_builtins::ffi::BashBuiltin };
-    let name_field_value = strings::to_cstr(&name);
 
     // Acquire lock to store builtin state.
     let store_access = quote! {
         match #global_state().lock() {
             Ok(lock) => lock,
+            #poisoned_lock
+        }
+    };
 
-            _ => {
-                ::bash_builtins::log::error("invalid internal state");
-                return RETVAL_ERROR;
+    // Return the exit code of the builtin from its result.
+    let exit_code = quote! {
+        Ok(()) => 0,
+
+        Err(e) => {
+            if e.print_on_return() {
+                ::bash_builtins::error!("{}", e);
             }
+
+            e.exit_code()
+        }
+    };
+
+    // The state is locked during the call, so a recursive invocation would
+    // cause a deadlock.
+    let recursion_check = if reentrant {
+        None
+    } else {
+        Some(quote! {
+            if #global_depth.load(SeqCst) > 0 {
+                ::bash_builtins::log::error("recursive invocation");
+                return ::bash_builtins::ffi::exit::EXECUTION_FAILURE;
+            }
+        })
+    };
+
+    let call_body = if reentrant {
+        // The lock is released before invoking the builtin, so it can be
+        // invoked again from the same call. All calls get a shared reference
+        // to the instance.
+        //
+        // `on_subshell` requires a mutable reference, so it is only invoked if
+        // there are no other calls in progress.
+        quote! {
+            let state: *const #dyn_builtin = {
+                let mut lock = #store_access;
+                #call_constructor
+                match &mut *lock {
+                    Some(state) => {
+                        if #global_depth.load(SeqCst) == 1 {
+                            let pid = ::std::process::id();
+                            if #global_pid.swap(pid, SeqCst) != pid {
+                                state.on_subshell();
+                            }
+                        }
+
+                        &**state
+                    }
+
+                    None => {
+                        ::bash_builtins::log::error("builtin not initialized");
+                        return RETVAL_ERROR;
+                    }
+                }
+            };
+
+            // The instance is not dropped while there are calls in progress.
+            match unsafe { (*state).call_shared(&mut args) } {
+                #exit_code
+            }
+        }
+    } else {
+        quote! {
+            let mut lock = #store_access;
+            #call_constructor
+            match (&mut *lock) {
+                Some(state) => {
+                    let pid = ::std::process::id();
+                    if #global_pid.swap(pid, SeqCst) != pid {
+                        state.on_subshell();
+                    }
+
+                    match state.call(&mut args) {
+                        #exit_code
+                    }
+                }
+
+                None => {
+                    ::bash_builtins::log::error("builtin not initialized");
+                    RETVAL_ERROR
+                }
+            }
+        }
+    };
+
+    // Lock in the unload function, if the mutex is poisoned.
+    let unload_poisoned_lock = if on_panic == OnPanic::Keep {
+        quote! { Err(poison) => Some(poison.into_inner()), }
+    } else {
+        quote! {
+            Err(poison) => {
+                // If the mutex is poisoned we don't trust the state of
+                // the builtin. In this case the old value is leaked.
+                let old_state = poison.into_inner().take();
+                ::std::mem::forget(old_state);
+                None
+            },
         }
     };
 
     let state_type = quote! {
         ::std::sync::Mutex<
             ::std::option::Option<
-                ::std::boxed::Box<dyn ::bash_builtins::Builtin>>>
+                ::std::boxed::Box<#dyn_builtin>>>
+    };
+
+    // Items for each name.
+    let name_items = names.iter().enumerate().map(|(index, name)| {
+        // Symbols expected by bash.
+        let struct_bash_symbol = format_ident!("{}_struct", name);
+        let load_bash_symbol = format_ident!("{}_builtin_load", name);
+        let unload_bash_symbol = format_ident!("{}_builtin_unload", name);
+
+        let builtin_func = format_ident!("__bash_builtin__func_{}", name);
+        let global_doc = format_ident!("__bash_builtin__doc_{}", name);
+
+        let name_field_value = strings::to_cstr(name);
+        let load_constructor = load_constructor(index, name);
+
+        // Documentation generated from the options.
+        let options_doc = args
+            .options
+            .as_ref()
+            .map(|options| OptionsDoc::new(&args, name, options, &global_doc, &struct_bash_symbol));
+
+        let options_doc_items = options_doc.as_ref().map(|d| &d.items);
+        let options_doc_load = options_doc.as_ref().map(|d| &d.load);
+        let options_doc_unload = options_doc.as_ref().map(|d| &d.unload);
+
+        quote! {
+            #[no_mangle]
+            #[doc(hidden)]
+            pub static mut #struct_bash_symbol: #struct_type = #struct_type {
+                name: #name_field_value,
+                function: #builtin_func,
+                flags: #(#flags)|*,
+                short_doc: #short_doc,
+                long_doc: #long_doc,
+                handle: ::std::ptr::null()
+            };
+
+            #options_doc_items
+
+            #[no_mangle]
+            #[doc(hidden)]
+            pub extern "C" fn #load_bash_symbol(
+                _: *const ::std::os::raw::c_char
+            ) -> ::std::os::raw::c_int {
+                use ::std::sync::atomic::Ordering::SeqCst;
+
+                const RETVAL_ERROR: ::std::os::raw::c_int = 0;
+
+                ::std::panic::catch_unwind(|| {
+                    let mut lock = #store_access;
+                    #load_constructor
+
+                    #global_loaded[#index].store(true, SeqCst);
+
+                    if let Some(state) = &mut *lock {
+                        state.on_load(#name);
+                    }
+
+                    #options_doc_load
+                    ::bash_builtins::panics::install_hook();
+                    1
+                }).unwrap_or(RETVAL_ERROR)
+            }
+
+            #[no_mangle]
+            #[doc(hidden)]
+            pub extern "C" fn #unload_bash_symbol(
+                _: *const ::std::os::raw::c_char
+            ) {
+                use ::std::sync::atomic::Ordering::SeqCst;
+
+                let _ = ::std::panic::catch_unwind(|| {
+                    #options_doc_unload
+                    ::bash_builtins::panics::uninstall_hook();
+                    #global_loaded[#index].store(false, SeqCst);
+
+                    if #global_loaded.iter().any(|loaded| loaded.load(SeqCst)) {
+                        return;
+                    }
+
+                    // If the builtin is removed by itself, the instance is
+                    // still in use, so it is leaked.
+                    if #global_depth.load(SeqCst) > 0 {
+                        return;
+                    }
+
+                    let lock = match #global_state().lock() {
+                        Ok(lock) => Some(lock),
+                        #unload_poisoned_lock
+                    };
+
+                    if let Some(mut state) = lock.and_then(|mut lock| lock.take()) {
+                        if let Err(e) = state.on_unload() {
+                            if e.print_on_return() {
+                                ::bash_builtins::log::warning(format!("{}: {}", #name, e));
+                            }
+                        }
+                    }
+
+                    #global_state_init.store(false, SeqCst);
+                });
+            }
+
+            extern "C" fn #builtin_func(
+                word_list: *const ::bash_builtins::ffi::WordList
+            ) -> ::std::os::raw::c_int {
+                #call_func(#name, word_list)
+            }
+        }
+    });
+
+    // Compare `long_doc` with the options. It is done only once, even if
+    // there are multiple names.
+    let options_checks = match (&args.options, &args.long_doc) {
+        (Some(options), Some(long_doc)) => Some(OptionsDoc::checks(long_doc, options)),
+        _ => None,
     };
 
     // Final code.
     let tokens = quote! {
-        #[no_mangle]
-        #[doc(hidden)]
-        pub static mut #struct_bash_symbol: #struct_type = #struct_type {
-            name: #name_field_value,
-            function: #builtin_func,
-            flags: ::bash_builtins::ffi::flags::BUILTIN_ENABLED,
-            short_doc: #short_doc,
-            long_doc: #long_doc,
-            handle: ::std::ptr::null()
-        };
+        #(#name_items)*
+
+        #options_checks
 
         #[doc(hidden)]
         static #global_state_init: ::std::sync::atomic::AtomicBool =
             ::std::sync::atomic::AtomicBool::new(false);
 
+        #[doc(hidden)]
+        static #global_loaded: [::std::sync::atomic::AtomicBool; #names_count] = {
+            #[allow(clippy::declare_interior_mutable_const)]
+            const NOT_LOADED: ::std::sync::atomic::AtomicBool =
+                ::std::sync::atomic::AtomicBool::new(false);
+
+            [NOT_LOADED; #names_count]
+        };
+
+        #[doc(hidden)]
+        static #global_names: [&str; #names_count] = [ #(#names),* ];
+
+        #[doc(hidden)]
+        static #global_shell_pid: ::std::sync::atomic::AtomicU32 =
+            ::std::sync::atomic::AtomicU32::new(0);
+
+        #[doc(hidden)]
+        static #global_pid: ::std::sync::atomic::AtomicU32 =
+            ::std::sync::atomic::AtomicU32::new(0);
+
+        #[doc(hidden)]
+        static #global_depth: ::std::sync::atomic::AtomicUsize =
+            ::std::sync::atomic::AtomicUsize::new(0);
+
+        #[doc(hidden)]
+        static #global_exit_hook: ::std::sync::Once = ::std::sync::Once::new();
+
+        #[doc(hidden)]
+        static mut #global_state_data: ::std::mem::MaybeUninit<#state_type> =
+            ::std::mem::MaybeUninit::uninit();
+
+        extern "C" fn #exit_func() {
+            use ::std::sync::atomic::Ordering::SeqCst;
+
+            let _ = ::std::panic::catch_unwind(|| {
+                // The shell can exit while the builtin is running (for
+                // example, if bash finds an error with `set -e`). In that
+                // case, the instance is in use, so the hook is skipped. The
+                // lock is not held by the call in reentrant mode, so the
+                // depth is checked too.
+                if !#global_state_init.load(SeqCst)
+                    || #global_shell_pid.load(SeqCst) != ::std::process::id()
+                    || #global_depth.load(SeqCst) > 0
+                {
+                    return;
+                }
+
+                if let Ok(mut lock) = #global_state().try_lock() {
+                    if let Some(state) = &mut *lock {
+                        state.on_shell_exit();
+                    }
+                }
+            });
+        }
+
         fn #global_state() -> &'static #state_type {
             use ::std::mem::MaybeUninit;
             use ::std::sync::{Mutex, Once, atomic::Ordering::SeqCst};
 
-            static mut STATE: MaybeUninit<#state_type> = MaybeUninit::uninit();
-
             if #global_state_init.fetch_or(true, SeqCst) == false {
                 unsafe {
-                    STATE = MaybeUninit::new(Mutex::new(None));
+                    #global_state_data = MaybeUninit::new(Mutex::new(None));
                 }
             }
 
-            unsafe { &*STATE.as_ptr() }
+            unsafe { &*::std::ptr::addr_of!(#global_state_data).cast::<#state_type>() }
         }
 
-        #[no_mangle]
-        #[doc(hidden)]
-        pub extern "C" fn #load_bash_symbol(
-            name: *const ::std::os::raw::c_char
-        ) -> ::std::os::raw::c_int {
-            const RETVAL_ERROR: ::std::os::raw::c_int = 0;
-            ::std::panic::catch_unwind(|| {
-                let mut lock = #store_access;
-                let state = #constructor as Box<dyn ::bash_builtins::Builtin>;
-                *lock = Some(state);
-                1
-            }).unwrap_or(RETVAL_ERROR)
-        }
+        // Bash can interrupt a call with a `longjmp` (for example, after a
+        // fatal error in an expansion), which skips the Rust frames. Then,
+        // the depth is never decremented, and the lock is never released.
+        //
+        // This function is registered as an unwind-protect during the call,
+        // so bash invokes it before the jump. If the lock is held by the
+        // interrupted call, the instance is moved to a new mutex. The old
+        // one is leaked, since it is still locked.
+        extern "C" fn #interrupted_func(_: *mut ::std::os::raw::c_void) {
+            use ::std::mem::MaybeUninit;
+            use ::std::sync::{Mutex, TryLockError, atomic::Ordering::SeqCst};
 
-        #[no_mangle]
-        #[doc(hidden)]
-        pub extern "C" fn #unload_bash_symbol(
-            name: *const ::std::os::raw::c_char
-        ) {
             let _ = ::std::panic::catch_unwind(|| {
-                match #global_state().lock() {
-                    Ok(mut lock) => { *lock = None },
-
-                    Err(poison) => {
-                        // If the mutex is poisoned we don't trust the state of
-                        // the builtin. In this case the old value is leaked.
-                        let old_state = poison.into_inner().take();
-                        ::std::mem::forget(old_state);
-                    },
-                };
-
-                #global_state_init.store(false, ::std::sync::atomic::Ordering::SeqCst);
+                if #global_depth.fetch_sub(1, SeqCst) != 1
+                    || !#global_state_init.load(SeqCst)
+                    || !matches!(#global_state().try_lock(), Err(TryLockError::WouldBlock))
+                {
+                    return;
+                }
+
+                unsafe {
+                    let data = &mut *::std::ptr::addr_of_mut!(#global_state_data);
+                    let state = match data.assume_init_mut().get_mut() {
+                        Ok(state) => state.take(),
+                        Err(poison) => poison.into_inner().take(),
+                    };
+
+                    *data = MaybeUninit::new(Mutex::new(state));
+                }
             });
         }
 
-        extern "C" fn #builtin_func(
+        fn #call_func(
+            name: &'static str,
             word_list: *const ::bash_builtins::ffi::WordList
         ) -> ::std::os::raw::c_int {
+            use ::std::sync::atomic::Ordering::SeqCst;
+
             const RETVAL_ERROR: ::std::os::raw::c_int = 1;
 
-            ::std::panic::catch_unwind(|| {
-                let mut lock = #store_access;
+            #recursion_check
+            #global_depth.fetch_add(1, SeqCst);
+            ::bash_builtins::ffi::unwind_protect(#interrupted_func);
+
+            let result = ::bash_builtins::panics::catch_unwind(|| {
                 let mut args = unsafe { ::bash_builtins::Args::new(word_list) };
-                match (&mut *lock) {
-                    Some(state) => {
-                        match state.call(&mut args) {
-                            Ok(()) => 0,
+                args.set_builtin_name(name);
+                #call_body
+            });
 
-                            Err(e) => {
-                                if e.print_on_return() {
-                                    ::bash_builtins::error!("{}", e);
-                                }
+            ::bash_builtins::ffi::discard_unwind_protect();
+            #global_depth.fetch_sub(1, SeqCst);
 
-                                e.exit_code()
-                            }
-                        }
-                    }
+            match result {
+                Ok(exit_code) => exit_code,
 
-                    None => {
-                        ::bash_builtins::log::error("builtin not initialized");
-                        RETVAL_ERROR
-                    }
+                Err(report) => {
+                    report.print();
+                    #panic_recovery
+                    101 // exit code on panic!(), from Rust
                 }
-            }).unwrap_or(101) // exit code on panic!(), from Rust
+            }
         }
+
+        #create_func_item
     };
 
     tokens.into()
 }
 
-mod args {
+/// Behaviour after a panic in the builtin, from the `on_panic` argument.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum OnPanic {
+    /// Create a new instance.
+    Reset,
+
+    /// Use the same instance.
+    Keep,
+
+    /// Don't use the instance anymore.
+    Poison,
+}
+
+impl OnPanic {
+    fn from_args(args: &args::MacroArgs) -> Result<Self, syn::Error> {
+        let value = match &args.on_panic {
+            Some(value) => value,
+            None => return Ok(OnPanic::Poison),
+        };
+
+        match value.value().as_str() {
+            "reset" => Ok(OnPanic::Reset),
+            "keep" => Ok(OnPanic::Keep),
+            "poison" => Ok(OnPanic::Poison),
+            _ => Err(syn::Error::new(
+                value.span(),
+                "expected one of: \"reset\", \"keep\", \"poison\"",
+            )),
+        }
+    }
+}
+
+/// Names of the builtin, from the `name` or the `names` arguments.
+fn builtin_names(args: &args::MacroArgs) -> Result<Vec<String>, syn::Error> {
+    let names: Vec<_> = match (&args.name, &args.names) {
+        (Some(name), None) => vec![name.clone()],
+        (None, Some(names)) => {
+            if names.items.is_empty() {
+                return Err(syn::Error::new(
+                    names.span,
+                    "`names` requires at least one name",
+                ));
+            }
+
+            names.items.iter().cloned().collect()
+        }
+
+        (Some(name), Some(_)) => {
+            return Err(syn::Error::new(
+                name.span(),
+                "`name` can't be used with `names`",
+            ))
+        }
+
+        (None, None) => {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "one of `name` or `names` is required",
+            ))
+        }
+    };
+
+    let mut found = std::collections::HashSet::new();
+    for name in &names {
+        if !found.insert(name.value()) {
+            return Err(syn::Error::new(
+                name.span(),
+                format!("duplicated name '{}'", name.value()),
+            ));
+        }
+    }
+
+    Ok(names.iter().map(|name| name.value()).collect())
+}
+
+/// Flags for the `flags` field of the builtin struct.
+///
+/// Some combinations are rejected, since bash does not define any builtin
+/// with them:
+///
+/// * `special` and `posix`: special builtins are already found before any
+///   other command.
+/// * `localvar` without `assignment`: local variables are created from
+///   assignment arguments, like in `local name=value`.
+/// * `localvar` and `special`: assignments before a special builtin persist
+///   after it, so they can't be local.
+fn builtin_flags(args: &args::MacroArgs) -> Result<Vec<proc_macro2::TokenStream>, syn::Error> {
+    fn enabled(flag: &Option<syn::LitBool>) -> Option<&syn::LitBool> {
+        flag.as_ref().filter(|f| f.value)
+    }
+
+    let assignment = enabled(&args.assignment);
+    let special = enabled(&args.special);
+    let posix = enabled(&args.posix);
+    let localvar = enabled(&args.localvar);
+
+    if let (Some(_), Some(posix)) = (special, posix) {
+        return Err(syn::Error::new(
+            posix.span(),
+            "`posix` can't be used with `special`",
+        ));
+    }
+
+    if let Some(localvar) = localvar {
+        if assignment.is_none() {
+            return Err(syn::Error::new(
+                localvar.span(),
+                "`localvar` requires `assignment = true`",
+            ));
+        }
+
+        if special.is_some() {
+            return Err(syn::Error::new(
+                localvar.span(),
+                "`localvar` can't be used with `special`",
+            ));
+        }
+    }
+
+    let mut flags = vec![quote! { ::bash_builtins::ffi::flags::BUILTIN_ENABLED }];
+
+    let optional_flags = [
+        (assignment, quote! { ASSIGNMENT_BUILTIN }),
+        (special, quote! { SPECIAL_BUILTIN }),
+        (posix, quote! { POSIX_BUILTIN }),
+        (localvar, quote! { LOCALVAR_BUILTIN }),
+    ];
+
+    for (enabled, flag) in optional_flags {
+        if enabled.is_some() {
+            flags.push(quote! { ::bash_builtins::ffi::flags::#flag });
+        }
+    }
+
+    Ok(flags)
+}
+
+/// Code to generate the documentation from the `options` argument.
+struct OptionsDoc {
+    /// Global items.
+    items: proc_macro2::TokenStream,
+
+    /// Statements for the load function.
+    load: proc_macro2::TokenStream,
+
+    /// Statements for the unload function.
+    unload: proc_macro2::TokenStream,
+}
+
+impl OptionsDoc {
+    fn new(
+        args: &args::MacroArgs,
+        name: &str,
+        options: &syn::Path,
+        global_doc: &syn::Ident,
+        struct_bash_symbol: &syn::Ident,
+    ) -> Self {
+        let short_doc = match &args.short_doc {
+            Some(s) => quote! { Some(#s) },
+            None => quote! { None },
+        };
+
+        let long_doc = match &args.long_doc {
+            Some(s) => {
+                let text = strings::long_doc_text(&s.value());
+                quote! { Some(#text) }
+            }
+
+            None => quote! { None },
+        };
+
+        let doc_type = quote! {
+            ::std::sync::Mutex<::std::option::Option<::bash_builtins::doc::BuiltinDoc>>
+        };
+
+        let items = quote! {
+            #[doc(hidden)]
+            static #global_doc: #doc_type = ::std::sync::Mutex::new(None);
+        };
+
+        // The documentation is updated before bash copies the struct to its
+        // own table of builtins.
+        let load = quote! {
+            let doc = ::bash_builtins::doc::BuiltinDoc::new::<#options>(#name, #short_doc, #long_doc);
+            unsafe { doc.apply(::std::ptr::addr_of_mut!(#struct_bash_symbol)) };
+            match #global_doc.lock() {
+                Ok(mut lock) => *lock = Some(doc),
+                Err(poison) => *poison.into_inner() = Some(doc),
+            }
+        };
+
+        let unload = quote! {
+            match #global_doc.lock() {
+                Ok(mut lock) => *lock = None,
+                Err(poison) => *poison.into_inner() = None,
+            }
+        };
+
+        OptionsDoc {
+            items,
+            load,
+            unload,
+        }
+    }
+
+    /// Compare the options documented in `long_doc` with the options accepted
+    /// by the parser.
+    ///
+    /// Differences are reported with warnings. They are emitted by calling a
+    /// deprecated function, selected by a constant evaluated at compile-time.
+    fn checks(long_doc: &syn::LitStr, options: &syn::Path) -> proc_macro2::TokenStream {
+        let documented = strings::documented_options(&long_doc.value());
+        let type_name = options.to_token_stream().to_string().replace(' ', "");
+
+        let options_type = quote! {
+            <#options as ::bash_builtins::doc::OptionsDoc>
+        };
+
+        let mut functions = Vec::new();
+        let mut notes = Vec::new();
+        let mut conditions = Vec::new();
+
+        for (index, name) in documented.iter().enumerate() {
+            functions.push(format_ident!("documented_option_{}", index));
+            notes.push(format!(
+                "`{}` is documented in `long_doc`, but `{}` does not accept it",
+                name, type_name,
+            ));
+            conditions.push(quote! {
+                ::bash_builtins::doc::has_option(#options_type::OPTIONS, #name)
+            });
+        }
+
+        functions.push(format_ident!("undocumented_options"));
+        notes.push(format!(
+            "some options of `{}` are not documented in `long_doc`",
+            type_name,
+        ));
+        conditions.push(quote! {
+            ::bash_builtins::doc::all_documented(#options_type::OPTIONS, &[ #(#documented),* ])
+        });
+
+        let calls = functions
+            .iter()
+            .zip(&conditions)
+            .map(|(function, condition)| {
+                quote_spanned! { long_doc.span() =>
+                    Check::<{ #condition }>::#function();
+                }
+            });
+
+        quote! {
+            const _: () = {
+                struct Check<const OK: bool>;
+
+                #[allow(dead_code)]
+                impl Check<true> {
+                    #(const fn #functions() {})*
+                }
+
+                #[allow(dead_code)]
+                impl Check<false> {
+                    #(
+                        #[deprecated(note = #notes)]
+                        const fn #functions() {}
+                    )*
+                }
+
+                #(#calls)*
+            };
+        }
+    }
+}
+
+pub(crate) mod args {
     //! Macro arguments.
 
     use syn::parse::{Parse, ParseStream, Result};
-    use syn::{ExprPath, LitStr, Token};
+    use syn::punctuated::Punctuated;
+    use syn::{bracketed, ExprPath, LitBool, LitStr, Path, Token};
 
     #[derive(Default)]
     pub(crate) struct MacroArgs {
         pub(crate) name: Option<LitStr>,
+        pub(crate) names: Option<LitStrList>,
         pub(crate) create: Option<ExprPath>,
         pub(crate) try_create: Option<ExprPath>,
         pub(crate) short_doc: Option<LitStr>,
         pub(crate) long_doc: Option<LitStr>,
+        pub(crate) options: Option<Path>,
+        pub(crate) assignment: Option<LitBool>,
+        pub(crate) special: Option<LitBool>,
+        pub(crate) posix: Option<LitBool>,
+        pub(crate) localvar: Option<LitBool>,
+        pub(crate) lazy: Option<LitBool>,
+        pub(crate) on_panic: Option<LitStr>,
+        pub(crate) reentrant: Option<LitBool>,
+    }
+
+    /// A list of string literals, like `["a", "b"]`.
+    pub(crate) struct LitStrList {
+        pub(crate) span: proc_macro2::Span,
+        pub(crate) items: Punctuated<LitStr, Token![,]>,
+    }
+
+    impl Parse for LitStrList {
+        fn parse(input: ParseStream) -> Result<Self> {
+            let content;
+            let bracket = bracketed!(content in input);
+            Ok(LitStrList {
+                span: bracket.span,
+                items: content.parse_terminated(<LitStr as Parse>::parse)?,
+            })
+        }
     }
 
     mod kw {
         syn::custom_keyword!(name);
+        syn::custom_keyword!(names);
         syn::custom_keyword!(create);
         syn::custom_keyword!(try_create);
         syn::custom_keyword!(short_doc);
         syn::custom_keyword!(long_doc);
+        syn::custom_keyword!(options);
+        syn::custom_keyword!(assignment);
+        syn::custom_keyword!(special);
+        syn::custom_keyword!(posix);
+        syn::custom_keyword!(localvar);
+        syn::custom_keyword!(lazy);
+        syn::custom_keyword!(on_panic);
+        syn::custom_keyword!(reentrant);
     }
 
     impl Parse for MacroArgs {
@@ -263,7 +1055,10 @@ mod args {
                     }
                 }
 
-                args!(name create try_create short_doc long_doc);
+                args!(
+                    name names create try_create short_doc long_doc options
+                    assignment special posix localvar lazy on_panic reentrant
+                );
 
                 if !input.is_empty() {
                     input.parse::<Token![,]>()?;
@@ -299,6 +1094,19 @@ mod strings {
     /// - The left margin used to indent the text in the source code is removed.
     /// - `DOC_INDENT` spaces are prepended after the first line.
     pub(crate) fn to_long_doc(text: &str) -> proc_macro2::TokenStream {
+        let lines = to_cstr(&long_doc_text(text));
+
+        quote! {
+            (&[
+                #lines,
+                ::std::ptr::null()
+            ]).as_ptr()
+        }
+    }
+
+    /// Text of a `long_doc` string, after the changes described in
+    /// `to_long_doc`.
+    pub(crate) fn long_doc_text(text: &str) -> String {
         let text = text.trim_start_matches('\n').trim_end();
 
         let left_margin = text
@@ -322,13 +1130,39 @@ mod strings {
             }
         }
 
-        let lines = to_cstr(&doc);
+        doc
+    }
 
-        quote! {
-            (&[
-                #lines,
-                ::std::ptr::null()
-            ]).as_ptr()
+    /// Options documented in a `long_doc` string.
+    ///
+    /// Options are expected at the beginning of a line, like `-a, --all` or
+    /// `+a`.
+    /// An argument after the name (`--name=value`) is ignored.
+    pub(crate) fn documented_options(text: &str) -> Vec<String> {
+        let mut options = Vec::new();
+
+        for line in text.lines() {
+            for word in line.split_whitespace() {
+                let is_plus = word.len() == 2
+                    && word.starts_with('+')
+                    && word[1..].chars().all(|c| c.is_ascii_alphanumeric());
+
+                if !word.starts_with('-') && !is_plus {
+                    break;
+                }
+
+                let name = word
+                    .trim_end_matches(',')
+                    .split(['=', '['])
+                    .next()
+                    .unwrap_or_default();
+
+                if name.len() > 1 && name != "--" {
+                    options.push(name.to_owned());
+                }
+            }
         }
+
+        options
     }
 }
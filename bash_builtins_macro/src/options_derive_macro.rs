@@ -2,34 +2,150 @@
 
 use proc_macro::TokenStream;
 use proc_macro2::Span;
-use quote::{quote, ToTokens};
+use quote::{format_ident, quote, ToTokens};
 use std::collections::HashSet;
 use std::os::raw::c_int;
 use syn::spanned::Spanned;
 
+/// First value used to identify options without a short name.
+///
+/// It is outside the range of the characters accepted by `getopt()`, so it
+/// can't collide with any short option.
+const LONG_OPTION_BASE: c_int = 0x100;
+
+/// Flag added to the value of options used as `+x`.
+///
+/// It must be the same value used by `RawOptions` in the `bash_builtins`
+/// crate.
+const PLUS_OPTION: c_int = 0x1_0000;
+
+/// An option accepted by the parser, from an enum variant or a struct field.
+struct OptionSpec {
+    option: c_int,
+    short: Option<char>,
+    plus: bool,
+    long: Option<String>,
+    argument_type: Option<syn::Type>,
+    value_name: String,
+    required: bool,
+    doc: String,
+}
+
 struct VariantOption {
-    option: char,
+    spec: OptionSpec,
     name: syn::Ident,
-    argument_type: Option<syn::Type>,
+}
+
+/// How a struct field is updated when its option is found.
+enum FieldKind {
+    /// `bool` fields. Set to `true`.
+    Flag,
+
+    /// Integer fields with `#[opt(count)]`. Incremented for every occurrence.
+    Count,
+
+    /// `Option<T>` fields. Contains the last value.
+    Optional,
+
+    /// `Vec<T>` fields. Contains every value.
+    Multiple,
+
+    /// Any other type. The option is required, and the field contains the
+    /// last value.
+    Required,
+}
+
+struct FieldOption {
+    spec: OptionSpec,
+    name: syn::Ident,
+    kind: FieldKind,
+    ty: syn::Type,
+    constraints: Constraints,
+}
+
+/// Reference to another option in `conflicts_with` and `requires`.
+enum OptionRef {
+    /// The letter of a short option.
+    Short(char),
+
+    /// The name of a field.
+    Field(String),
+}
+
+/// Constraints checked after all options are parsed.
+#[derive(Default)]
+struct Constraints {
+    required: bool,
+    conflicts_with: Vec<OptionRef>,
+    requires: Vec<OptionRef>,
+    span: Option<Span>,
+}
+
+/// How a positional argument (`#[arg]`) is converted.
+enum ArgumentKind {
+    /// `Option<T>` fields. The argument can be omitted.
+    Optional,
+
+    /// `Vec<T>` fields. Contains every remaining argument.
+    Multiple,
+
+    /// Any other type. The argument is required.
+    Required,
+}
+
+struct FieldArgument {
+    name: syn::Ident,
+    kind: ArgumentKind,
+
+    /// Type used to convert the argument. For `Option<T>` and `Vec<T>` it is
+    /// the inner `T`.
+    ty: syn::Type,
+}
+
+/// Values in the `#[opt]` attribute.
+struct OptAttr {
+    short: Option<char>,
+    plus: bool,
+    long: Option<String>,
+    count: bool,
+    value_name: Option<String>,
+    constraints: Constraints,
 }
 
 pub(crate) fn macro_impl(args: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(args as syn::DeriveInput);
 
+    let tokens = match &input.data {
+        syn::Data::Enum(data) => enum_impl(&input, data),
+        syn::Data::Struct(data) => struct_impl(&input, data),
+        _ => Err(syn::Error::new(
+            input.span(),
+            "expected an enum or a struct with named fields",
+        )),
+    };
+
+    match tokens {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+/// Generate the `BuiltinOptions` implementation for an enum.
+fn enum_impl(
+    input: &syn::DeriveInput,
+    data: &syn::DataEnum,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
     // For each variant in the enum we generate a match branch. It checks
     // the result from `getops()`, and converts the argument (if any) to
     // the expected type.
 
-    let variants = match parse_variants(&input) {
-        Ok(v) => v,
-        Err(e) => return e.into_compile_error().into(),
-    };
+    let variants = parse_variants(data)?;
 
     let match_variants = variants.iter().map(|variant| {
-        let option = variant.option as c_int;
+        let option = variant.spec.option;
         let var_name = &variant.name;
 
-        let parser = match &variant.argument_type {
+        let parser = match &variant.spec.argument_type {
             None => {
                 quote! { Ok(Self::#var_name) }
             }
@@ -47,84 +163,33 @@ pub(crate) fn macro_impl(args: TokenStream) -> TokenStream {
         }
     });
 
-    // Build the string argument for `getopt()`.
-    //
-    // If an option has an argument, `getopt()` expects either a ':' (for
-    // required arguments) or a ';' (otherwise) after the option character.
-    //
-    // To select the extra character we use the constant `OPTSTR_ARGUMENT`,
-    // associated to the `FromWordPointer` instance. For `Option<T>` this
-    // constant is `;`, and for everything else it is ':'.
-    //
-    // The associated constant allows us to detect the `Option` type even if
-    // the user renames it in their code.
-    let options_string = {
-        let mut opts = Vec::new();
-
-        for variant in &variants {
-            let opt_byte = variant.option as u8;
-            opts.push(quote! { #opt_byte });
-
-            if let Some(argument_type) = &variant.argument_type {
-                let argument_type = remove_lifetimes(argument_type);
-
-                opts.push(quote! {
-                    <#argument_type as ::bash_builtins::convert::FromWordPointer>::OPTSTR_ARGUMENT
-                });
-            }
-        }
-
-        opts.push(quote! { 0 });
-        opts
-    };
-
-    let options_string_len = options_string.len();
-
-    // Add '__bash_builtin__cstr to the generic parameters.
-    //
-    // This lifetime is used to bound the `CStr` instances to the `&mut Args`
-    // variable received in `Builtin::call`.
-    let generics_ext = {
-        let mut generics = input.generics.clone();
-
-        let lifetime = syn::Lifetime::new("'__bash_builtin__cstr", Span::call_site());
-        generics
-            .params
-            .push(syn::GenericParam::Lifetime(syn::LifetimeDef::new(lifetime)));
-
-        for lt in input.generics.lifetimes() {
-            let where_clause = generics
-                .where_clause
-                .get_or_insert_with(|| syn::WhereClause {
-                    where_token: syn::token::Where {
-                        span: Span::call_site(),
-                    },
-                    predicates: syn::punctuated::Punctuated::new(),
-                });
-
-            let pred = format!("'__bash_builtin__cstr: '{}", lt.lifetime.ident);
-            where_clause.predicates.push(syn::parse_str(&pred).unwrap());
-        }
-
-        generics
-    };
+    let specs: Vec<_> = variants.iter().map(|v| &v.spec).collect();
+    let options_string = options_string(&specs);
+    let long_options = long_options(&specs);
+    let options_doc = options_doc(input, &specs, "");
 
     // Generate the parser.
 
+    let generics_ext = generics_ext(&input.generics);
     let (_, ty_generics, _) = input.generics.split_for_impl();
     let (impl_generics, _, where_clause) = generics_ext.split_for_impl();
 
     let type_name = &input.ident;
 
-    let tokens = quote! {
+    Ok(quote! {
         impl #impl_generics ::bash_builtins::BuiltinOptions<'__bash_builtin__cstr> for #type_name #ty_generics
         #where_clause
         {
             fn options() -> &'static [u8] {
-                const OPTIONS: [u8; #options_string_len] = [ #(#options_string,)* ];
+                #options_string
                 &OPTIONS[..]
             }
 
+            fn long_options() -> &'static [::bash_builtins::LongOption] {
+                #long_options
+                LONG_OPTIONS
+            }
+
             fn from_option(
                 opt: ::std::os::raw::c_int,
                 arg: Option<&'__bash_builtin__cstr ::std::ffi::CStr>,
@@ -133,35 +198,515 @@ pub(crate) fn macro_impl(args: TokenStream) -> TokenStream {
                     #(#match_variants,)*
 
                     _ =>  {
+                        ::bash_builtins::log::invalid_option();
                         ::bash_builtins::log::show_usage();
                         return Err(::bash_builtins::Error::Usage);
                     },
                 }
             }
         }
-    };
 
-    tokens.into()
+        #options_doc
+    })
 }
 
-/// Parse the macro input to extract variants data.
-fn parse_variants(input: &syn::DeriveInput) -> Result<Vec<VariantOption>, syn::Error> {
-    let mut found_options = HashSet::new();
+/// Generate the `FromArgs` implementation for a struct.
+///
+/// Every field is a local variable in `from_args`. They are updated for each
+/// option found by the parser, and then moved to the new instance.
+fn struct_impl(
+    input: &syn::DeriveInput,
+    data: &syn::DataStruct,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let (fields, arguments) = parse_fields(input, data)?;
+
+    let local = |name: &syn::Ident| {
+        format_ident!("__bash_builtin__field_{}", name, span = Span::call_site())
+    };
+
+    let locals = fields.iter().map(|field| {
+        let local = local(&field.name);
+        let ty = &field.ty;
+
+        match field.kind {
+            FieldKind::Flag => quote! { let mut #local = false; },
+            FieldKind::Count => quote! { let mut #local: #ty = 0; },
+            FieldKind::Required => quote! { let mut #local: Option<#ty> = None; },
+            FieldKind::Optional | FieldKind::Multiple => {
+                quote! { let mut #local: #ty = ::std::default::Default::default(); }
+            }
+        }
+    });
+
+    let match_fields = fields.iter().map(|field| {
+        let local = local(&field.name);
+        let option = field.spec.option;
+
+        let value = field.spec.argument_type.as_ref().map(|argument_type| {
+            quote! {
+                <#argument_type as ::bash_builtins::convert::FromWordPointer>::extract_value(
+                    __bash_builtin__arg
+                )?
+            }
+        });
+
+        let update = match field.kind {
+            FieldKind::Flag => quote! { #local = true },
+            FieldKind::Count => quote! { #local = #local.saturating_add(1) },
+            FieldKind::Optional | FieldKind::Required => quote! { #local = Some(#value) },
+            FieldKind::Multiple => quote! { #local.push(#value) },
+        };
+
+        quote! {
+            #option => { #update; }
+        }
+    });
+
+    let struct_fields = fields.iter().map(|field| {
+        let local = local(&field.name);
+        let name = &field.name;
+
+        match field.kind {
+            FieldKind::Required => {
+                let option_name = option_name(&field.spec);
+                quote! {
+                    #name: match #local {
+                        Some(value) => value,
+                        None => {
+                            ::bash_builtins::log::error(
+                                concat!(#option_name, ": option is required")
+                            );
+                            return Err(::bash_builtins::Error::Usage);
+                        }
+                    }
+                }
+            }
+
+            _ => quote! { #name: #local },
+        }
+    });
+
+    // Constraints between options.
+    let presence = |field: &FieldOption| {
+        let local = local(&field.name);
+        match field.kind {
+            FieldKind::Flag => quote! { #local },
+            FieldKind::Count => quote! { (#local != 0) },
+            FieldKind::Optional | FieldKind::Required => quote! { #local.is_some() },
+            FieldKind::Multiple => quote! { !#local.is_empty() },
+        }
+    };
+
+    let mut constraints = Vec::new();
+    let mut conflicts = HashSet::new();
+
+    for field in &fields {
+        let name = option_name(&field.spec);
+        let present = presence(field);
+
+        if field.constraints.required && !matches!(field.kind, FieldKind::Required) {
+            constraints.push(quote! {
+                if !#present {
+                    ::bash_builtins::log::error(concat!(#name, ": option is required"));
+                    return Err(::bash_builtins::Error::Usage);
+                }
+            });
+        }
+
+        for reference in &field.constraints.conflicts_with {
+            let other = find_option(&fields, reference).expect("valid reference");
+            let other_name = option_name(&other.spec);
+
+            // The same conflict can be declared in both fields.
+            let mut pair = [name.clone(), other_name.clone()];
+            pair.sort();
+            if !conflicts.insert(pair) {
+                continue;
+            }
+
+            let other_present = presence(other);
+            constraints.push(quote! {
+                if #present && #other_present {
+                    ::bash_builtins::log::error(
+                        concat!(#name, " and ", #other_name, " cannot be used together")
+                    );
+                    return Err(::bash_builtins::Error::Usage);
+                }
+            });
+        }
+
+        for reference in &field.constraints.requires {
+            let other = find_option(&fields, reference).expect("valid reference");
+            let other_name = option_name(&other.spec);
+            let other_present = presence(other);
+
+            constraints.push(quote! {
+                if #present && !#other_present {
+                    ::bash_builtins::log::error(concat!(#name, " requires ", #other_name));
+                    return Err(::bash_builtins::Error::Usage);
+                }
+            });
+        }
+    }
+
+    // Positional arguments are converted after the options, in the same
+    // order of the fields.
+    let parse_arguments = if arguments.is_empty() {
+        // Without positional arguments, the remaining words are rejected if
+        // the struct is the parser of a subcommand.
+        Some(quote! { __bash_builtin__options.finished_in_subcommand()?; })
+    } else {
+        let items = arguments.iter().map(|argument| {
+            let local = local(&argument.name);
+            let ty = &argument.ty;
+
+            let method = match argument.kind {
+                ArgumentKind::Required => quote! { required },
+                ArgumentKind::Optional => quote! { optional },
+                ArgumentKind::Multiple => quote! { remaining },
+            };
+
+            quote! {
+                let #local = __bash_builtin__arguments.#method::<#ty>()?;
+            }
+        });
 
-    let data = match &input.data {
-        syn::Data::Enum(d) => d,
-        _ => return Err(syn::Error::new(input.span(), "expected an enum")),
+        let finished = if arguments
+            .iter()
+            .any(|a| matches!(a.kind, ArgumentKind::Multiple))
+        {
+            None
+        } else {
+            Some(quote! { __bash_builtin__arguments.finished()?; })
+        };
+
+        Some(quote! {
+            let mut __bash_builtin__arguments = __bash_builtin__options.arguments();
+            #(#items)*
+            #finished
+        })
     };
 
-    data.variants
+    let argument_fields = arguments.iter().map(|argument| {
+        let local = local(&argument.name);
+        let name = &argument.name;
+        quote! { #name: #local }
+    });
+
+    let specs: Vec<_> = fields.iter().map(|f| &f.spec).collect();
+    let options_string = options_string(&specs);
+    let long_options = long_options(&specs);
+    let options_doc = options_doc(input, &specs, &arguments_usage(&arguments));
+
+    let generics_ext = generics_ext(&input.generics);
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let (impl_generics, _, where_clause) = generics_ext.split_for_impl();
+
+    let type_name = &input.ident;
+
+    Ok(quote! {
+        impl #impl_generics ::bash_builtins::FromArgs<'__bash_builtin__cstr> for #type_name #ty_generics
+        #where_clause
+        {
+            fn from_args(
+                __bash_builtin__args: &'__bash_builtin__cstr mut ::bash_builtins::Args,
+            ) -> ::bash_builtins::Result<Self> {
+                #options_string
+                #long_options
+
+                #(#locals)*
+
+                let mut __bash_builtin__options =
+                    __bash_builtin__args.raw_options(&OPTIONS, LONG_OPTIONS);
+
+                for __bash_builtin__opt in &mut __bash_builtin__options {
+                    let (__bash_builtin__opt, __bash_builtin__arg) = __bash_builtin__opt?;
+                    match __bash_builtin__opt {
+                        #(#match_fields,)*
+
+                        _ =>  {
+                            ::bash_builtins::log::invalid_option();
+                            ::bash_builtins::log::show_usage();
+                            return Err(::bash_builtins::Error::Usage);
+                        },
+                    }
+                }
+
+                #(#constraints)*
+
+                #parse_arguments
+
+                Ok(Self {
+                    #(#struct_fields,)*
+                    #(#argument_fields,)*
+                })
+            }
+        }
+
+        #options_doc
+    })
+}
+
+/// Build the string argument for `getopt()`, in a constant named `OPTIONS`.
+///
+/// If an option has an argument, `getopt()` expects either a ':' (for
+/// required arguments) or a ';' (otherwise) after the option character.
+///
+/// To select the extra character we use the constant `OPTSTR_ARGUMENT`,
+/// associated to the `FromWordPointer` instance. For `Option<T>` this
+/// constant is `;`, and for everything else it is ':'.
+///
+/// The associated constant allows us to detect the `Option` type even if
+/// the user renames it in their code.
+///
+/// If there are options used as `+x`, the string starts with a `+`. A letter
+/// used as both `-x` and `+x` is added only once.
+fn options_string(specs: &[&OptionSpec]) -> proc_macro2::TokenStream {
+    let mut opts = Vec::new();
+    let mut letters = HashSet::new();
+
+    if specs.iter().any(|spec| spec.plus) {
+        opts.push(quote! { b'+' });
+    }
+
+    for spec in specs {
+        let opt_byte = match spec.short {
+            Some(short) if letters.insert(short) => short as u8,
+            _ => continue,
+        };
+
+        opts.push(quote! { #opt_byte });
+
+        if let Some(argument_type) = &spec.argument_type {
+            let argument_type = remove_lifetimes(argument_type);
+
+            opts.push(quote! {
+                <#argument_type as ::bash_builtins::convert::FromWordPointer>::OPTSTR_ARGUMENT
+            });
+        }
+    }
+
+    opts.push(quote! { 0 });
+
+    let options_string_len = opts.len();
+
+    quote! {
+        const OPTIONS: [u8; #options_string_len] = [ #(#opts,)* ];
+    }
+}
+
+/// Table for long options (`--name` and `--name=value`), in a constant named
+/// `LONG_OPTIONS`.
+///
+/// They are parsed outside of `getopt()`, so the table includes the value
+/// to send to `from_option()`, and the same `OPTSTR_ARGUMENT` used in the
+/// options string.
+fn long_options(specs: &[&OptionSpec]) -> proc_macro2::TokenStream {
+    let items = specs.iter().filter_map(|spec| {
+        let long = spec.long.as_ref()?;
+        let option = spec.option;
+
+        let argument = match &spec.argument_type {
+            None => quote! { 0 },
+
+            Some(argument_type) => {
+                let argument_type = remove_lifetimes(argument_type);
+                quote! {
+                    <#argument_type as ::bash_builtins::convert::FromWordPointer>::OPTSTR_ARGUMENT
+                }
+            }
+        };
+
+        Some(quote! {
+            ::bash_builtins::LongOption {
+                name: #long,
+                opt: #option,
+                argument: #argument,
+            }
+        })
+    });
+
+    quote! {
+        const LONG_OPTIONS: &[::bash_builtins::LongOption] = &[ #(#items,)* ];
+    }
+}
+
+/// Implementation of `OptionsDoc`, used by `builtin_metadata!()` to generate
+/// the documentation of the builtin.
+fn options_doc(
+    input: &syn::DeriveInput,
+    specs: &[&OptionSpec],
+    arguments: &str,
+) -> proc_macro2::TokenStream {
+    let items = specs.iter().map(|spec| {
+        let short = match spec.short {
+            Some(short) => quote! { Some(#short) },
+            None => quote! { None },
+        };
+
+        let plus = spec.plus;
+
+        let long = match &spec.long {
+            Some(long) => quote! { Some(#long) },
+            None => quote! { None },
+        };
+
+        let (argument, choices) = match &spec.argument_type {
+            None => (quote! { 0 }, quote! { &[] }),
+
+            Some(argument_type) => {
+                let argument_type = remove_lifetimes(argument_type);
+                let trait_impl = quote! {
+                    <#argument_type as ::bash_builtins::convert::FromWordPointer>
+                };
+
+                (
+                    quote! { #trait_impl::OPTSTR_ARGUMENT },
+                    quote! { #trait_impl::CHOICES },
+                )
+            }
+        };
+
+        let value_name = &spec.value_name;
+        let required = spec.required;
+        let doc = &spec.doc;
+
+        quote! {
+            ::bash_builtins::doc::OptionDoc {
+                short: #short,
+                plus: #plus,
+                long: #long,
+                value_name: #value_name,
+                argument: #argument,
+                required: #required,
+                choices: #choices,
+                doc: #doc,
+            }
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let type_name = &input.ident;
+
+    quote! {
+        impl #impl_generics ::bash_builtins::doc::OptionsDoc for #type_name #ty_generics
+        #where_clause
+        {
+            const OPTIONS: &'static [::bash_builtins::doc::OptionDoc] = &[ #(#items,)* ];
+
+            const ARGUMENTS: &'static str = #arguments;
+        }
+    }
+}
+
+/// Usage of the positional arguments, like `path [count ...]`.
+fn arguments_usage(arguments: &[FieldArgument]) -> String {
+    let items: Vec<_> = arguments
         .iter()
-        .map(|v| parse_variant(v, &mut found_options))
-        .collect()
+        .map(|argument| {
+            let name = argument.name.to_string();
+            let name = name.trim_start_matches("r#");
+            match argument.kind {
+                ArgumentKind::Required => name.to_owned(),
+                ArgumentKind::Optional => format!("[{}]", name),
+                ArgumentKind::Multiple => format!("[{} ...]", name),
+            }
+        })
+        .collect();
+
+    items.join(" ")
+}
+
+/// Name of the option to be used in error messages.
+fn option_name(spec: &OptionSpec) -> String {
+    match (spec.short, &spec.long) {
+        (Some(short), _) if spec.plus => format!("+{}", short),
+        (Some(short), _) => format!("-{}", short),
+        (None, Some(long)) => format!("--{}", long),
+        (None, None) => unreachable!(),
+    }
+}
+
+/// Add '__bash_builtin__cstr to the generic parameters.
+///
+/// This lifetime is used to bound the `CStr` instances to the `&mut Args`
+/// variable received in `Builtin::call`.
+pub(crate) fn generics_ext(input_generics: &syn::Generics) -> syn::Generics {
+    let mut generics = input_generics.clone();
+
+    let lifetime = syn::Lifetime::new("'__bash_builtin__cstr", Span::call_site());
+    generics
+        .params
+        .push(syn::GenericParam::Lifetime(syn::LifetimeDef::new(lifetime)));
+
+    for lt in input_generics.lifetimes() {
+        let where_clause = generics
+            .where_clause
+            .get_or_insert_with(|| syn::WhereClause {
+                where_token: syn::token::Where {
+                    span: Span::call_site(),
+                },
+                predicates: syn::punctuated::Punctuated::new(),
+            });
+
+        let pred = format!("'__bash_builtin__cstr: '{}", lt.lifetime.ident);
+        where_clause.predicates.push(syn::parse_str(&pred).unwrap());
+    }
+
+    generics
+}
+
+/// Options found in the macro input, to detect duplicates.
+#[derive(Default)]
+struct FoundOptions {
+    short: HashSet<char>,
+    plus: HashSet<char>,
+    long: HashSet<String>,
+}
+
+/// Parse the macro input to extract variants data.
+fn parse_variants(data: &syn::DataEnum) -> Result<Vec<VariantOption>, syn::Error> {
+    let mut found_options = FoundOptions::default();
+
+    let variants: Vec<_> = data
+        .variants
+        .iter()
+        .enumerate()
+        .map(|(index, v)| parse_variant(v, index, &mut found_options))
+        .collect::<Result<_, _>>()?;
+
+    let specs: Vec<_> = variants.iter().map(|v| (&v.spec, v.name.span())).collect();
+    check_plus_options(&specs)?;
+
+    Ok(variants)
+}
+
+/// Options used as both `-x` and `+x` share the same entry in the string for
+/// `getopt()`, so both must have an argument, or none.
+fn check_plus_options(specs: &[(&OptionSpec, Span)]) -> Result<(), syn::Error> {
+    for (plus, span) in specs.iter().filter(|(spec, _)| spec.plus) {
+        let minus = specs
+            .iter()
+            .find(|(spec, _)| !spec.plus && spec.short == plus.short);
+
+        if let Some((minus, _)) = minus {
+            if minus.argument_type.is_some() != plus.argument_type.is_some() {
+                let short = plus.short.unwrap_or_default();
+                return Err(syn::Error::new(
+                    *span,
+                    format!("-{0} and +{0} must both have an argument, or none", short),
+                ));
+            }
+        }
+    }
+
+    Ok(())
 }
 
 fn parse_variant(
     variant: &syn::Variant,
-    found_options: &mut HashSet<char>,
+    index: usize,
+    found_options: &mut FoundOptions,
 ) -> Result<VariantOption, syn::Error> {
     let name = variant.ident.clone();
 
@@ -171,33 +716,23 @@ fn parse_variant(
         };
     }
 
-    let option = variant
-        .attrs
-        .iter()
-        .find(|attr| attr.path.is_ident("opt"))
-        .ok_or_else(|| syn::Error::new(variant.span(), "missing #[opt = '…'] attribute"))
-        .and_then(|attr| attr.parse_meta())
-        .and_then(|meta| {
-            let value = match meta {
-                syn::Meta::NameValue(value) => value,
-                _ => err!("invalid #[opt] attribute"),
-            };
+    if variant.attrs.iter().any(|attr| attr.path.is_ident("arg")) {
+        err!("#[arg] is only valid in struct fields");
+    }
 
-            let opt = match value.lit {
-                syn::Lit::Char(lit) => lit.value(),
-                _ => err!("#[opt = '…'] requires a character"),
-            };
+    let attr = parse_opt_attr(&variant.attrs, variant.span(), found_options)?;
 
-            if !opt.is_ascii_alphanumeric() {
-                err!("#[opt] requires an ASCII alphanumeric character");
-            }
-
-            if !found_options.insert(opt) {
-                err!(format!("duplicated option '{}'", opt));
-            }
+    if attr.count {
+        err!("#[opt(count)] is only valid in struct fields");
+    }
 
-            Ok(opt)
-        })?;
+    let constraints = &attr.constraints;
+    if constraints.required
+        || !constraints.conflicts_with.is_empty()
+        || !constraints.requires.is_empty()
+    {
+        err!("#[opt(required, conflicts_with, requires)] are only valid in struct fields");
+    }
 
     let argument_type = match &variant.fields {
         syn::Fields::Unit => None,
@@ -217,12 +752,398 @@ fn parse_variant(
     };
 
     Ok(VariantOption {
-        option,
+        spec: option_spec(
+            attr,
+            index,
+            argument_type,
+            "value".to_owned(),
+            &variant.attrs,
+        ),
         name,
-        argument_type,
     })
 }
 
+/// Parse the fields of a struct.
+///
+/// Fields with the `#[arg]` attribute are positional arguments. Any other
+/// field is an option.
+fn parse_fields(
+    input: &syn::DeriveInput,
+    data: &syn::DataStruct,
+) -> Result<(Vec<FieldOption>, Vec<FieldArgument>), syn::Error> {
+    let mut found_options = FoundOptions::default();
+
+    let fields = match &data.fields {
+        syn::Fields::Named(fields) => &fields.named,
+        syn::Fields::Unit => return Ok((Vec::new(), Vec::new())),
+        syn::Fields::Unnamed(_) => {
+            return Err(syn::Error::new(
+                input.span(),
+                "expected an enum or a struct with named fields",
+            ))
+        }
+    };
+
+    let mut options = Vec::new();
+    let mut arguments = Vec::new();
+
+    for (index, field) in fields.iter().enumerate() {
+        let span = field.span();
+        let name = field.ident.clone().expect("named field");
+
+        if let Some(attr) = field.attrs.iter().find(|attr| attr.path.is_ident("arg")) {
+            arguments.push(parse_argument(field, attr, name, &arguments)?);
+            continue;
+        }
+
+        let mut attr = parse_opt_attr(&field.attrs, span, &mut found_options)?;
+        let constraints = std::mem::take(&mut attr.constraints);
+
+        let (kind, argument_type) = match type_wrapper(&field.ty) {
+            _ if attr.count => (FieldKind::Count, None),
+            Some(("Option", inner)) => (FieldKind::Optional, Some(inner.clone())),
+            Some(("Vec", inner)) => (FieldKind::Multiple, Some(inner.clone())),
+            _ if is_bool(&field.ty) => (FieldKind::Flag, None),
+            _ => (FieldKind::Required, Some(field.ty.clone())),
+        };
+
+        if attr.count && (is_bool(&field.ty) || type_wrapper(&field.ty).is_some()) {
+            return Err(syn::Error::new(
+                span,
+                "#[opt(count)] requires an integer type",
+            ));
+        }
+
+        let value_name = name.to_string().trim_start_matches("r#").to_owned();
+        let mut spec = option_spec(attr, index, argument_type, value_name, &field.attrs);
+        spec.required = constraints.required || matches!(kind, FieldKind::Required);
+
+        options.push(FieldOption {
+            spec,
+            name,
+            kind,
+            ty: field.ty.clone(),
+            constraints,
+        });
+    }
+
+    let specs: Vec<_> = options.iter().map(|o| (&o.spec, o.name.span())).collect();
+    check_plus_options(&specs)?;
+
+    // References in `conflicts_with` and `requires` must be valid options.
+    for option in &options {
+        let references = option
+            .constraints
+            .conflicts_with
+            .iter()
+            .chain(&option.constraints.requires);
+
+        for reference in references {
+            if find_option(&options, reference).is_none() {
+                let span = option.constraints.span.unwrap_or_else(Span::call_site);
+                let msg = match reference {
+                    OptionRef::Short(c) => format!("unknown option '{}'", c),
+                    OptionRef::Field(f) => format!("unknown field '{}'", f),
+                };
+
+                return Err(syn::Error::new(span, msg));
+            }
+        }
+    }
+
+    Ok((options, arguments))
+}
+
+/// Find the option referenced in a `conflicts_with` or `requires` attribute.
+fn find_option<'a>(options: &'a [FieldOption], reference: &OptionRef) -> Option<&'a FieldOption> {
+    options.iter().find(|option| match reference {
+        OptionRef::Short(c) => !option.spec.plus && option.spec.short == Some(*c),
+        OptionRef::Field(f) => option.name == f,
+    })
+}
+
+/// Parse a field with the `#[arg]` attribute.
+///
+/// Required arguments must be before optional ones, and a `Vec<T>` has to be
+/// the last argument.
+fn parse_argument(
+    field: &syn::Field,
+    attr: &syn::Attribute,
+    name: syn::Ident,
+    previous: &[FieldArgument],
+) -> Result<FieldArgument, syn::Error> {
+    macro_rules! err {
+        ($err:expr) => {
+            return Err(syn::Error::new(field.span(), $err))
+        };
+    }
+
+    if !attr.tokens.is_empty() {
+        err!("invalid #[arg] attribute");
+    }
+
+    if field.attrs.iter().any(|attr| attr.path.is_ident("opt")) {
+        err!("#[arg] and #[opt] can't be used in the same field");
+    }
+
+    let (kind, ty) = match type_wrapper(&field.ty) {
+        Some(("Option", inner)) => (ArgumentKind::Optional, inner.clone()),
+        Some(("Vec", inner)) => (ArgumentKind::Multiple, inner.clone()),
+        _ => (ArgumentKind::Required, field.ty.clone()),
+    };
+
+    for argument in previous {
+        match (&argument.kind, &kind) {
+            (ArgumentKind::Multiple, _) => err!("#[arg] with `Vec<T>` must be the last argument"),
+
+            (ArgumentKind::Optional, ArgumentKind::Required) => {
+                err!("required arguments must be before optional arguments")
+            }
+
+            _ => (),
+        }
+    }
+
+    Ok(FieldArgument { name, kind, ty })
+}
+
+/// Parse the `#[opt]` attribute of a variant or a field.
+fn parse_opt_attr(
+    attrs: &[syn::Attribute],
+    span: Span,
+    found_options: &mut FoundOptions,
+) -> Result<OptAttr, syn::Error> {
+    macro_rules! err {
+        ($err:expr) => {
+            return Err(syn::Error::new(span, $err))
+        };
+    }
+
+    let meta = attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("opt"))
+        .ok_or_else(|| syn::Error::new(span, "missing #[opt = '…'] attribute"))
+        .and_then(|attr| attr.parse_meta())?;
+
+    let mut attr = OptAttr {
+        short: None,
+        plus: false,
+        long: None,
+        count: false,
+        value_name: None,
+        constraints: Constraints::default(),
+    };
+
+    match meta {
+        // #[opt = 'x']
+        syn::Meta::NameValue(value) => {
+            attr.short = match value.lit {
+                syn::Lit::Char(lit) => Some(lit.value()),
+                _ => err!("#[opt = '…'] requires a character"),
+            };
+        }
+
+        // #[opt(short = 'x', long = "name")]
+        syn::Meta::List(list) => {
+            for nested in list.nested {
+                let value = match nested {
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(value)) => value,
+
+                    // #[opt('x')] is equivalent to #[opt(short = 'x')]
+                    syn::NestedMeta::Lit(syn::Lit::Char(lit)) if attr.short.is_none() => {
+                        attr.short = Some(lit.value());
+                        continue;
+                    }
+
+                    syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("count") => {
+                        attr.count = true;
+                        continue;
+                    }
+
+                    syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("required") => {
+                        attr.constraints.required = true;
+                        continue;
+                    }
+
+                    syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("plus") => {
+                        attr.plus = true;
+                        continue;
+                    }
+
+                    _ => err!("invalid #[opt] attribute"),
+                };
+
+                // #[opt(conflicts_with = 'x', requires = "field")]
+                let is_conflict = value.path.is_ident("conflicts_with");
+                if is_conflict || value.path.is_ident("requires") {
+                    let reference = match &value.lit {
+                        syn::Lit::Char(lit) => OptionRef::Short(lit.value()),
+                        syn::Lit::Str(lit) => OptionRef::Field(lit.value()),
+                        _ => err!(
+                            "#[opt(conflicts_with, requires)] require a character or a field name"
+                        ),
+                    };
+
+                    let constraints = &mut attr.constraints;
+                    constraints.span = Some(value.span());
+
+                    if is_conflict {
+                        constraints.conflicts_with.push(reference);
+                    } else {
+                        constraints.requires.push(reference);
+                    }
+
+                    continue;
+                }
+
+                match value.lit {
+                    syn::Lit::Char(lit) if value.path.is_ident("short") => {
+                        attr.short = Some(lit.value());
+                    }
+
+                    syn::Lit::Str(lit) if value.path.is_ident("long") => {
+                        attr.long = Some(lit.value());
+                    }
+
+                    syn::Lit::Str(lit) if value.path.is_ident("value_name") => {
+                        attr.value_name = Some(lit.value());
+                    }
+
+                    _ if value.path.is_ident("short") => {
+                        err!("#[opt(short = '…')] requires a character")
+                    }
+
+                    _ if value.path.is_ident("long") => {
+                        err!("#[opt(long = \"…\")] requires a string")
+                    }
+
+                    _ if value.path.is_ident("value_name") => {
+                        err!("#[opt(value_name = \"…\")] requires a string")
+                    }
+
+                    _ => err!("invalid #[opt] attribute"),
+                }
+            }
+
+            if attr.short.is_none() && attr.long.is_none() {
+                err!("#[opt(…)] requires `short` or `long`");
+            }
+
+            if attr.plus && (attr.short.is_none() || attr.long.is_some()) {
+                err!("#[opt(plus)] requires `short`, and can't be used with `long`");
+            }
+        }
+
+        _ => err!("invalid #[opt] attribute"),
+    }
+
+    if let Some(opt) = attr.short {
+        if !opt.is_ascii_alphanumeric() {
+            err!("#[opt] requires an ASCII alphanumeric character");
+        }
+
+        if attr.plus {
+            if !found_options.plus.insert(opt) {
+                err!(format!("duplicated option '+{}'", opt));
+            }
+        } else if !found_options.short.insert(opt) {
+            err!(format!("duplicated option '{}'", opt));
+        }
+    }
+
+    if let Some(opt) = &attr.long {
+        let valid = opt.starts_with(|c: char| c.is_ascii_alphanumeric())
+            && opt.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+
+        if !valid {
+            err!("long options require ASCII alphanumeric characters or '-'");
+        }
+
+        if opt == "help" {
+            err!("--help is reserved for the builtin help");
+        }
+
+        if !found_options.long.insert(opt.clone()) {
+            err!(format!("duplicated option '--{}'", opt));
+        }
+    }
+
+    Ok(attr)
+}
+
+fn option_spec(
+    attr: OptAttr,
+    index: usize,
+    argument_type: Option<syn::Type>,
+    default_value_name: String,
+    attrs: &[syn::Attribute],
+) -> OptionSpec {
+    let option = match attr.short {
+        Some(short) if attr.plus => short as c_int | PLUS_OPTION,
+        Some(short) => short as c_int,
+        None => LONG_OPTION_BASE + index as c_int,
+    };
+
+    OptionSpec {
+        option,
+        short: attr.short,
+        plus: attr.plus,
+        long: attr.long,
+        argument_type,
+        value_name: attr.value_name.unwrap_or(default_value_name),
+        required: false,
+        doc: doc_comments(attrs),
+    }
+}
+
+/// Extract the text of the doc comments (`/// …`) in `attrs`.
+///
+/// Lines are joined with a space.
+pub(crate) fn doc_comments(attrs: &[syn::Attribute]) -> String {
+    let lines: Vec<_> = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(lit),
+                ..
+            })) => Some(lit.value().trim().to_owned()),
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    lines.join(" ")
+}
+
+/// If `ty` is a path with a single generic argument (like `Option<T>` or
+/// `Vec<T>`), returns the name of the type and its argument.
+fn type_wrapper(ty: &syn::Type) -> Option<(&'static str, &syn::Type)> {
+    let segment = match ty {
+        syn::Type::Path(path) if path.qself.is_none() => path.path.segments.last()?,
+        _ => return None,
+    };
+
+    let name = ["Option", "Vec"]
+        .iter()
+        .find(|name| segment.ident == name)?;
+
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+            match args.args.first() {
+                Some(syn::GenericArgument::Type(inner)) => Some((name, inner)),
+                _ => None,
+            }
+        }
+
+        _ => None,
+    }
+}
+
+fn is_bool(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(path) if path.qself.is_none() && path.path.is_ident("bool"))
+}
+
 /// Replace every `'lifetime` with a `'_`.
 ///
 /// The conversion is done using find-and-replace against the text
@@ -0,0 +1,158 @@
+//! Implementation of the `FromWordPointer` derive macro.
+
+use crate::commands_derive_macro::kebab_case;
+use proc_macro::TokenStream;
+use quote::quote;
+use std::collections::HashSet;
+use syn::spanned::Spanned;
+
+/// A valid value, from an enum variant.
+struct Choice {
+    variant: syn::Ident,
+
+    /// Name shown in the help and in error messages.
+    name: String,
+
+    /// Other accepted names.
+    aliases: Vec<String>,
+}
+
+pub(crate) fn macro_impl(args: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(args as syn::DeriveInput);
+
+    let tokens = match &input.data {
+        syn::Data::Enum(data) => enum_impl(&input, data),
+        _ => Err(syn::Error::new(input.span(), "expected an enum")),
+    };
+
+    match tokens {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+/// Generate the `FromWordPointer` implementation.
+///
+/// Values are compared with the bytes of the argument, so non-UTF-8 arguments
+/// are reported as invalid values.
+fn enum_impl(
+    input: &syn::DeriveInput,
+    data: &syn::DataEnum,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "generic parameters are not supported",
+        ));
+    }
+
+    let mut found_names = HashSet::new();
+
+    let choices = data
+        .variants
+        .iter()
+        .map(|variant| parse_variant(variant, &mut found_names))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let match_choices = choices.iter().map(|choice| {
+        let variant = &choice.variant;
+        let names = Some(&choice.name)
+            .into_iter()
+            .chain(&choice.aliases)
+            .map(|name| syn::LitByteStr::new(name.as_bytes(), variant.span()));
+
+        quote! {
+            #(#names)|* => ::std::result::Result::Ok(Self::#variant)
+        }
+    });
+
+    let names = choices.iter().map(|choice| &choice.name);
+
+    let type_name = &input.ident;
+
+    Ok(quote! {
+        impl<'__bash_builtin__cstr> ::bash_builtins::convert::FromWordPointer<'__bash_builtin__cstr>
+            for #type_name
+        {
+            type Err = ::bash_builtins::convert::InvalidChoice;
+
+            const CHOICES: &'static [&'static str] = &[ #(#names),* ];
+
+            fn from_cstr(
+                s: &'__bash_builtin__cstr ::std::ffi::CStr,
+            ) -> ::std::result::Result<Self, Self::Err> {
+                match s.to_bytes() {
+                    #(#match_choices,)*
+                    _ => ::std::result::Result::Err(
+                        ::bash_builtins::convert::InvalidChoice::new(s, Self::CHOICES)
+                    ),
+                }
+            }
+
+            fn print_error(_: &::std::ffi::CStr, error: &Self::Err) {
+                ::bash_builtins::error!("{}", error);
+            }
+        }
+    })
+}
+
+fn parse_variant(
+    variant: &syn::Variant,
+    found_names: &mut HashSet<String>,
+) -> Result<Choice, syn::Error> {
+    macro_rules! err {
+        ($err:expr) => {
+            return Err(syn::Error::new(variant.span(), $err))
+        };
+    }
+
+    if !matches!(variant.fields, syn::Fields::Unit) {
+        err!("only unit variants are supported");
+    }
+
+    let mut choice = Choice {
+        variant: variant.ident.clone(),
+        name: kebab_case(&variant.ident.to_string()),
+        aliases: Vec::new(),
+    };
+
+    // #[choice(name = "…", alias = "…")]
+    for attr in variant.attrs.iter().filter(|a| a.path.is_ident("choice")) {
+        let list = match attr.parse_meta()? {
+            syn::Meta::List(list) => list,
+            _ => err!("expected #[choice(name = \"…\", alias = \"…\")]"),
+        };
+
+        for nested in list.nested {
+            match nested {
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(lit),
+                    ..
+                })) => {
+                    if path.is_ident("name") {
+                        choice.name = lit.value();
+                    } else if path.is_ident("alias") {
+                        choice.aliases.push(lit.value());
+                    } else {
+                        err!("invalid #[choice] attribute");
+                    }
+                }
+
+                _ => err!("invalid #[choice] attribute"),
+            }
+        }
+    }
+
+    for name in Some(&choice.name).into_iter().chain(&choice.aliases) {
+        if name.is_empty() {
+            err!("empty names are not allowed");
+        }
+
+        if !found_names.insert(name.clone()) {
+            err!(format!("duplicated value '{}'", name));
+        }
+    }
+
+    Ok(choice)
+}
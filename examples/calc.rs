@@ -0,0 +1,38 @@
+//! Bash builtin to evaluate arithmetic expressions.
+
+use bash_builtins::arith::{self, Arith};
+use bash_builtins::{builtin_metadata, Args, Builtin, BuiltinOptions, Result};
+use std::io::{self, BufWriter, Write};
+
+builtin_metadata!(
+    name = "calc",
+    create = Calc::default,
+    long_doc = "Print the result of each expression.",
+    options = Opt,
+);
+
+#[derive(BuiltinOptions)]
+struct Opt {
+    /// Maximum number of expressions.
+    #[opt(short = 'l', value_name = "limit")]
+    limit: Option<Arith<u8>>,
+}
+
+#[derive(Default)]
+struct Calc;
+
+impl Builtin for Calc {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        let opt: Opt = args.parse()?;
+        let limit = opt.limit.map_or(usize::MAX, |l| l.into_inner().into());
+
+        let stdout_handle = io::stdout();
+        let mut output = BufWriter::new(stdout_handle.lock());
+
+        for expr in args.string_arguments().take(limit) {
+            writeln!(&mut output, "{}", arith::eval(expr?)?)?;
+        }
+
+        Ok(())
+    }
+}
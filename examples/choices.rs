@@ -0,0 +1,50 @@
+//! Bash builtin with arguments from a fixed list of values.
+
+use bash_builtins::{builtin_metadata, Args, Builtin, BuiltinOptions, FromWordPointer, Result};
+use std::io::{self, BufWriter, Write};
+
+builtin_metadata!(
+    name = "choices",
+    create = Choices::default,
+    long_doc = "Print the selected format and the arguments.",
+    options = Opt,
+);
+
+#[allow(dead_code)]
+#[derive(FromWordPointer, Debug)]
+enum Format {
+    Json,
+
+    #[choice(alias = "plain")]
+    Text,
+
+    #[choice(name = "tsv", alias = "tab")]
+    TabSeparated,
+}
+
+#[allow(dead_code)]
+#[derive(BuiltinOptions, Debug)]
+struct Opt {
+    /// Output format.
+    #[opt(short = 'f', long = "format")]
+    format: Option<Format>,
+
+    #[arg]
+    formats: Vec<Format>,
+}
+
+#[derive(Default)]
+struct Choices;
+
+impl Builtin for Choices {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        let opt: Opt = args.parse()?;
+
+        let stdout_handle = io::stdout();
+        let mut output = BufWriter::new(stdout_handle.lock());
+
+        writeln!(&mut output, "{:?}", opt)?;
+
+        Ok(())
+    }
+}
@@ -6,25 +6,21 @@ use std::io::{stdout, Write};
 builtin_metadata!(
     name = "counter",
     create = Counter::default,
-    short_doc = "counter [-r] [-s value] [-a value]",
-    long_doc = "
-        Print a value, and increment it.
-
-        Options:
-          -r\tReset the value to 0.
-          -s\tSet the counter to a specific value.
-          -a\tIncrement the counter by a value.
-    ",
+    long_doc = "Print a value, and increment it.",
+    options = Opt,
 );
 
 #[derive(BuiltinOptions)]
 enum Opt {
+    /// Reset the value to 0.
     #[opt = 'r']
     Reset,
 
+    /// Set the counter to a specific value.
     #[opt = 's']
     Set(isize),
 
+    /// Increment the counter by a value.
     #[opt = 'a']
     Add(isize),
 }
@@ -18,30 +18,32 @@ builtin_metadata!(
         Exit Status:
         Returns 0 if all files can be read; non-zero otherwise.
     ",
+    options = Opt,
 );
 
 #[derive(Default)]
 struct FileSize;
 
 #[derive(BuiltinOptions)]
-enum Opt {
-    #[opt = 'k']
-    Kilobytes,
+struct Opt {
+    #[opt(short = 'k', conflicts_with = 'm')]
+    kilobytes: bool,
 
     #[opt = 'm']
-    Megabytes,
+    megabytes: bool,
 }
 
 impl Builtin for FileSize {
     fn call(&mut self, args: &mut Args) -> Result<()> {
-        let mut scale = 1;
+        let opt: Opt = args.parse()?;
 
-        for opt in args.options() {
-            scale = match opt? {
-                Opt::Kilobytes => 1 << 10,
-                Opt::Megabytes => 1 << 20,
-            }
-        }
+        let scale = if opt.kilobytes {
+            1 << 10
+        } else if opt.megabytes {
+            1 << 20
+        } else {
+            1
+        };
 
         let stdout_handle = io::stdout();
         let mut output = BufWriter::new(stdout_handle.lock());
@@ -0,0 +1,90 @@
+//! Bash builtin with subcommands to manage a key-value store.
+
+use bash_builtins::{builtin_metadata, Args, Builtin, BuiltinCommands, BuiltinOptions, Result};
+use std::collections::BTreeMap;
+use std::io::{self, BufWriter, Write};
+
+builtin_metadata!(
+    name = "kv",
+    create = KeyValue::default,
+    long_doc = "Manage a key-value store.",
+    options = Command,
+);
+
+#[derive(BuiltinCommands)]
+enum Command<'a> {
+    /// Print the value of a key.
+    Get(GetOpt<'a>),
+
+    /// Set the value of a key.
+    Set(SetOpt),
+
+    /// Print all keys.
+    List,
+
+    /// Remove all keys.
+    Clear(ClearOpt),
+}
+
+#[derive(BuiltinOptions)]
+struct GetOpt<'a> {
+    /// Value to print if the key is missing.
+    #[opt = 'd']
+    default: Option<&'a str>,
+
+    #[arg]
+    key: &'a str,
+}
+
+#[derive(BuiltinOptions)]
+struct SetOpt {
+    #[arg]
+    key: String,
+
+    #[arg]
+    value: String,
+}
+
+#[derive(BuiltinOptions)]
+struct ClearOpt {
+    /// Print the removed keys.
+    #[opt = 'v']
+    verbose: bool,
+}
+
+#[derive(Default)]
+struct KeyValue(BTreeMap<String, String>);
+
+impl Builtin for KeyValue {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        let stdout_handle = io::stdout();
+        let mut output = BufWriter::new(stdout_handle.lock());
+
+        match args.parse()? {
+            Command::Get(opt) => match self.0.get(opt.key).map(String::as_str).or(opt.default) {
+                Some(value) => writeln!(&mut output, "{}", value)?,
+                None => return Err(bash_builtins::Error::ExitCode(1)),
+            },
+
+            Command::Set(opt) => {
+                self.0.insert(opt.key, opt.value);
+            }
+
+            Command::List => {
+                for key in self.0.keys() {
+                    writeln!(&mut output, "{}", key)?;
+                }
+            }
+
+            Command::Clear(opt) => {
+                for (key, _) in std::mem::take(&mut self.0) {
+                    if opt.verbose {
+                        writeln!(&mut output, "removed {}", key)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
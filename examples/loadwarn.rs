@@ -0,0 +1,39 @@
+//! Bash builtin that can be loaded with a warning.
+
+use bash_builtins::{builtin_metadata, variables, Args, Builtin, Result, WithWarning};
+use std::io::{stdout, Write};
+
+builtin_metadata!(name = "loadwarn", try_create = LoadWarn::new);
+
+struct LoadWarn {
+    config: Option<String>,
+}
+
+impl LoadWarn {
+    // Fails if `$LOADWARN_FAIL` is set, and shows a warning if
+    // `$LOADWARN_CONFIG` is missing.
+    fn new() -> std::result::Result<WithWarning<Self>, &'static str> {
+        if variables::find_as_string("LOADWARN_FAIL").is_some() {
+            return Err("can't load the builtin");
+        }
+
+        let config = variables::find_as_string("LOADWARN_CONFIG")
+            .map(|config| config.to_string_lossy().into_owned());
+
+        Ok(match config {
+            Some(config) => WithWarning::none(LoadWarn {
+                config: Some(config),
+            }),
+
+            None => WithWarning::new(LoadWarn { config: None }, "$LOADWARN_CONFIG is not set"),
+        })
+    }
+}
+
+impl Builtin for LoadWarn {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        args.no_options()?;
+        writeln!(stdout(), "config: {:?}", self.config)?;
+        Ok(())
+    }
+}
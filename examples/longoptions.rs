@@ -0,0 +1,47 @@
+//! Bash builtin with long options.
+
+use bash_builtins::{builtin_metadata, Args, Builtin, BuiltinOptions, Result};
+use std::io::{self, BufWriter, Write};
+
+builtin_metadata!(
+    name = "longoptions",
+    create = LongOptions::default,
+    short_doc = "longoptions [-v] [-l limit] [--name name] [--color[=when]] [args]",
+);
+
+#[allow(dead_code)]
+#[derive(BuiltinOptions, Debug)]
+enum Opt<'a> {
+    #[opt(short = 'v', long = "verbose")]
+    Verbose,
+
+    #[opt(short = 'l', long = "limit")]
+    Limit(u32),
+
+    #[opt(long = "name")]
+    Name(&'a str),
+
+    #[opt(long = "color")]
+    Color(Option<&'a str>),
+}
+
+#[derive(Default)]
+struct LongOptions;
+
+impl Builtin for LongOptions {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        let stdout_handle = io::stdout();
+        let mut output = BufWriter::new(stdout_handle.lock());
+
+        writeln!(&mut output, " -")?;
+        for opt in args.options::<Opt>() {
+            writeln!(&mut output, "{:?}", opt?)?;
+        }
+
+        for arg in args.string_arguments() {
+            writeln!(&mut output, "arg: {}", arg?)?;
+        }
+
+        Ok(())
+    }
+}
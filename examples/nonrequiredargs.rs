@@ -5,6 +5,7 @@ use std::io::{self, BufWriter, Write};
 
 builtin_metadata!(name = "nonrequiredargs", create = NonRequiredArgs::default);
 
+#[allow(dead_code)]
 #[derive(BuiltinOptions, Debug)]
 enum Opt<'a> {
     #[opt = 'f']
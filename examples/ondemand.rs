@@ -0,0 +1,30 @@
+//! Bash builtin that is created on its first call.
+
+use bash_builtins::{builtin_metadata, variables, Args, Builtin, Result};
+use std::io::{stdout, Write};
+
+builtin_metadata!(name = "ondemand", try_create = OnDemand::new, lazy = true,);
+
+struct OnDemand(usize);
+
+impl OnDemand {
+    // Fails if `$ONDEMAND_FAIL` is set, so the builtin is loaded, but it can't
+    // be executed.
+    fn new() -> std::result::Result<Self, &'static str> {
+        if variables::find_as_string("ONDEMAND_FAIL").is_some() {
+            return Err("can't create the instance");
+        }
+
+        let _ = writeln!(stdout(), "[create]");
+        Ok(OnDemand(0))
+    }
+}
+
+impl Builtin for OnDemand {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        args.no_options()?;
+        self.0 += 1;
+        writeln!(stdout(), "{}", self.0)?;
+        Ok(())
+    }
+}
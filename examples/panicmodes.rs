@@ -0,0 +1,35 @@
+//! Bash builtins to recover from a panic.
+//!
+//! `panicreset` creates a new instance after a panic, and `panickeep` keeps
+//! using the same instance.
+
+use bash_builtins::{builtin_metadata, Args, Builtin, Result};
+use std::io::{stdout, Write};
+
+builtin_metadata!(
+    name = "panicreset",
+    create = Counter::default,
+    on_panic = "reset",
+);
+
+builtin_metadata!(
+    name = "panickeep",
+    create = Counter::default,
+    on_panic = "keep",
+);
+
+#[derive(Default)]
+struct Counter(usize);
+
+impl Builtin for Counter {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        self.0 += 1;
+
+        if args.string_arguments().any(|a| a == Ok("panic")) {
+            panic!("DO PANIC");
+        }
+
+        writeln!(stdout(), "{}", self.0)?;
+        Ok(())
+    }
+}
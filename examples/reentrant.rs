@@ -0,0 +1,99 @@
+//! Bash builtins that can be invoked while they are running.
+//!
+//! Both builtins evaluate arithmetic expressions, which can invoke the same
+//! builtin in a command substitution, like `a[$(reentrant 1)]`.
+//!
+//! `nonreentrant` uses the default mode, so the nested call fails.
+//! `reentrant` is defined with `reentrant = true`, so the nested call gets a
+//! shared reference to the same instance.
+//!
+//! `interrupted` is used to test calls interrupted by bash.
+
+use bash_builtins::{
+    arith, builtin_metadata, Args, Builtin, BuiltinOptions, Result, SharedBuiltin,
+};
+use std::cell::Cell;
+use std::io::{stderr, stdout, Write};
+use std::os::raw::c_int;
+
+builtin_metadata!(name = "nonreentrant", create = Depth::default);
+
+builtin_metadata!(
+    name = "reentrant",
+    create = Depth::default,
+    reentrant = true
+);
+
+builtin_metadata!(name = "interrupted", create = Interrupted::default);
+
+#[derive(Default)]
+struct Depth(Cell<usize>);
+
+#[derive(Default)]
+struct Interrupted;
+
+extern "C" {
+    fn top_level_cleanup();
+    fn jump_to_top_level(_: c_int);
+}
+
+impl Builtin for Interrupted {
+    // With `-j`, jump to the top level of bash, like after a fatal error in
+    // an expansion. The next calls must work as usual.
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        let opt: Opt = args.parse()?;
+        args.finished()?;
+        writeln!(stdout(), "interrupted: call")?;
+
+        if opt.jump {
+            const DISCARD: c_int = 2;
+            unsafe {
+                top_level_cleanup();
+                jump_to_top_level(DISCARD);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_shell_exit(&mut self) {
+        let _ = writeln!(stdout(), "interrupted: shell exit");
+    }
+}
+
+#[derive(BuiltinOptions)]
+struct Opt {
+    #[opt = 'j']
+    jump: bool,
+}
+
+impl Builtin for Depth {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        self.call_shared(args)
+    }
+}
+
+impl SharedBuiltin for Depth {
+    fn call_shared(&self, args: &mut Args) -> Result<()> {
+        args.no_options()?;
+
+        self.0.set(self.0.get() + 1);
+        let result = self.eval(args);
+        self.0.set(self.0.get() - 1);
+
+        result
+    }
+}
+
+impl Depth {
+    // Print the result of each expression, and the depth to stderr.
+    fn eval(&self, args: &mut Args) -> Result<()> {
+        for expr in args.string_arguments() {
+            let value = arith::eval(expr?)?;
+            writeln!(stderr(), "depth {}", self.0.get())?;
+            writeln!(stdout(), "{}", value)?;
+        }
+
+        Ok(())
+    }
+}
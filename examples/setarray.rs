@@ -0,0 +1,42 @@
+//! Bash builtin to replace the contents of an array.
+
+use bash_builtins::{builtin, variables, Error, Result};
+
+/// Replace the contents of the array NAME with VALUES.
+#[builtin(name = "setarray")]
+fn setarray(
+    #[opt(
+        short = 'a',
+        conflicts_with = 'A',
+        help = "Append the values to the array."
+    )]
+    append: bool,
+    #[opt(
+        short = 'A',
+        help = "Create an associative array from KEY=VALUE items."
+    )]
+    assoc: bool,
+    name: String,
+    values: Vec<String>,
+) -> Result<()> {
+    if assoc {
+        let items = values
+            .iter()
+            .map(|item| {
+                let mut parts = item.splitn(2, '=');
+                match (parts.next(), parts.next()) {
+                    (Some(key), Some(value)) => Ok((key, value)),
+                    _ => Err(Error::Usage),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        variables::set_assoc(&name, items)?;
+    } else if append {
+        variables::append_array(&name, &values)?;
+    } else {
+        variables::set_array(&name, &values)?;
+    }
+
+    Ok(())
+}
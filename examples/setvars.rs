@@ -0,0 +1,33 @@
+//! Bash builtin to assign variables, like `declare`.
+
+use bash_builtins::{builtin_metadata, warning, Args, Builtin, Result};
+
+builtin_metadata!(
+    name = "setvars",
+    create = SetVars::default,
+    short_doc = "setvars name[=value] ...",
+    long_doc = "Assign a value to each variable.",
+    assignment = true,
+);
+
+#[derive(Default)]
+struct SetVars;
+
+impl Builtin for SetVars {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        args.no_options()?;
+
+        for assignment in args.assignments() {
+            let assignment = assignment?;
+
+            if assignment.value().is_none() {
+                warning!("{}: missing value", assignment.name());
+                continue;
+            }
+
+            assignment.assign()?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+//! Bash builtin with arguments for signals, file descriptors, modes, users,
+//! groups, durations and sizes.
+
+use bash_builtins::convert::{ByteSize, Fd, Group, Mode, Signal, User};
+use bash_builtins::{builtin_metadata, Args, Builtin, BuiltinOptions, Result};
+use std::io::{self, BufWriter, Write};
+use std::time::Duration;
+
+builtin_metadata!(
+    name = "shelltypes",
+    create = ShellTypes::default,
+    long_doc = "Print the values of the options.",
+    options = Opt,
+);
+
+#[derive(BuiltinOptions)]
+struct Opt {
+    /// Signal to send.
+    #[opt = 's']
+    signal: Option<Signal>,
+
+    /// Output file descriptor.
+    #[opt = 'u']
+    fd: Option<Fd>,
+
+    /// Mode to apply to 0644.
+    #[opt = 'm']
+    mode: Option<Mode>,
+
+    /// Owner.
+    #[opt = 'o']
+    user: Option<User>,
+
+    /// Group.
+    #[opt = 'g']
+    group: Option<Group>,
+
+    /// Timeout.
+    #[opt = 't']
+    timeout: Option<Duration>,
+
+    /// Size limit.
+    #[opt = 'b']
+    size: Option<ByteSize>,
+}
+
+#[derive(Default)]
+struct ShellTypes;
+
+impl Builtin for ShellTypes {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        let opt: Opt = args.parse()?;
+        args.finished()?;
+
+        let stdout_handle = io::stdout();
+        let mut output = BufWriter::new(stdout_handle.lock());
+
+        if let Some(signal) = opt.signal {
+            writeln!(&mut output, "signal: {} ({})", signal, signal.number())?;
+        }
+
+        if let Some(fd) = opt.fd {
+            writeln!(&mut output, "fd: {} (open: {})", fd, fd.is_open())?;
+        }
+
+        if let Some(mode) = opt.mode {
+            writeln!(&mut output, "mode: {} -> {:o}", mode, mode.apply(0o644))?;
+        }
+
+        if let Some(user) = opt.user {
+            writeln!(&mut output, "uid: {}", user.uid())?;
+        }
+
+        if let Some(group) = opt.group {
+            writeln!(&mut output, "gid: {}", group.gid())?;
+        }
+
+        if let Some(timeout) = opt.timeout {
+            writeln!(&mut output, "timeout: {:?}", timeout)?;
+        }
+
+        if let Some(size) = opt.size {
+            writeln!(&mut output, "size: {}", size)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+//! POSIX special builtin that does nothing, like `:`.
+
+use bash_builtins::{builtin_metadata, Args, Builtin, Result};
+
+builtin_metadata!(
+    name = "special",
+    create = Special::default,
+    short_doc = "special [arg ...]",
+    long_doc = "Do nothing, like the `:` builtin.",
+    special = true,
+);
+
+#[derive(Default)]
+struct Special;
+
+impl Builtin for Special {
+    fn call(&mut self, _args: &mut Args) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+//! Bash builtins to manage a stack, sharing the same instance.
+
+use bash_builtins::{builtin_metadata, Args, Builtin, Error, Result};
+use std::io::{stdout, Write};
+
+builtin_metadata!(
+    names = ["stack", "push", "pop"],
+    create = Stack::default,
+    short_doc = "stack | push [value ...] | pop",
+    long_doc = "
+        Manage a stack of strings.
+
+        `push` adds the values to the stack, `pop` removes the last one
+        and prints it, and `stack` prints all of them.
+    ",
+);
+
+#[derive(Default)]
+struct Stack(Vec<String>);
+
+impl Builtin for Stack {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        args.no_options()?;
+
+        match args.builtin_name() {
+            "push" => {
+                for value in args.string_arguments() {
+                    self.0.push(value?.to_owned());
+                }
+            }
+
+            "pop" => {
+                args.finished()?;
+
+                match self.0.pop() {
+                    Some(value) => writeln!(stdout(), "{}", value)?,
+                    None => return Err(Error::ExitCode(1)),
+                }
+            }
+
+            _ => {
+                args.finished()?;
+
+                for value in &self.0 {
+                    writeln!(stdout(), "{}", value)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Stack {
+    fn drop(&mut self) {
+        let _ = writeln!(stdout(), "[drop] {:?}", self.0);
+    }
+}
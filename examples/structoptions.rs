@@ -0,0 +1,64 @@
+//! Bash builtin with options parsed into a struct.
+
+use bash_builtins::{builtin_metadata, Args, Builtin, BuiltinOptions, Result};
+use std::io::{self, BufWriter, Write};
+
+builtin_metadata!(
+    name = "structoptions",
+    create = StructOptions::default,
+    short_doc = "structoptions [-q] [-v ...] [-n name] [-i path ...] [-x] [+x] -l limit [args]",
+    long_doc = "Print the options and the arguments.",
+    options = Opt,
+);
+
+#[allow(dead_code)]
+#[derive(BuiltinOptions, Debug)]
+struct Opt<'a> {
+    /// Don't print anything.
+    #[opt(short = 'q', long = "quiet")]
+    quiet: bool,
+
+    /// Increase verbosity.
+    #[opt(short = 'v', count)]
+    verbose: u8,
+
+    /// Set the name.
+    #[opt = 'n']
+    name: Option<&'a str>,
+
+    /// Add a path.
+    #[opt(short = 'i', long = "include")]
+    include: Vec<std::path::PathBuf>,
+
+    /// Show extended information. Requires a name.
+    #[opt(short = 'x', requires = "name")]
+    extended: bool,
+
+    /// Hide extended information.
+    #[opt(short = 'x', plus)]
+    no_extended: bool,
+
+    /// Set the limit.
+    #[opt(short = 'l', long = "limit")]
+    limit: u32,
+}
+
+#[derive(Default)]
+struct StructOptions;
+
+impl Builtin for StructOptions {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        let opt: Opt = args.parse()?;
+
+        let stdout_handle = io::stdout();
+        let mut output = BufWriter::new(stdout_handle.lock());
+
+        writeln!(&mut output, "{:?}", opt)?;
+
+        for arg in args.string_arguments() {
+            writeln!(&mut output, "arg: {}", arg?)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+//! Bash builtin to print the lifecycle events of its instance.
+
+use bash_builtins::{builtin_metadata, variables, Args, Builtin, Error, Result};
+use std::io::{stdout, Write};
+
+builtin_metadata!(name = "tracehooks", create = TraceHooks::default);
+
+#[derive(Default)]
+struct TraceHooks;
+
+impl Builtin for TraceHooks {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        args.no_options()?;
+        writeln!(stdout(), "call")?;
+        Ok(())
+    }
+
+    fn on_load(&mut self, name: &str) {
+        let _ = writeln!(stdout(), "[load] {}", name);
+    }
+
+    // A warning is printed if `$TRACEHOOKS_BUSY` is set.
+    fn on_unload(&mut self) -> Result<()> {
+        writeln!(stdout(), "[unload]")?;
+
+        if variables::find_as_string("TRACEHOOKS_BUSY").is_some() {
+            return Err(Error::GenericError("instance still in use".into()));
+        }
+
+        Ok(())
+    }
+
+    fn on_shell_exit(&mut self) {
+        let _ = writeln!(stdout(), "[shell exit]");
+    }
+
+    fn on_subshell(&mut self) {
+        let _ = writeln!(stdout(), "[subshell]");
+    }
+}
+
+impl Drop for TraceHooks {
+    fn drop(&mut self) {
+        let _ = writeln!(stdout(), "[drop]");
+    }
+}
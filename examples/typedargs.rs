@@ -0,0 +1,42 @@
+//! Bash builtin with typed positional arguments.
+
+use bash_builtins::{builtin_metadata, Args, Builtin, BuiltinOptions, Result};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+builtin_metadata!(
+    name = "typedargs",
+    create = TypedArgs::default,
+    long_doc = "Print the options and the arguments.",
+    options = Opt,
+);
+
+#[allow(dead_code)]
+#[derive(BuiltinOptions, Debug)]
+struct Opt {
+    /// Show more details.
+    #[opt = 'v']
+    verbose: bool,
+
+    #[arg]
+    path: PathBuf,
+
+    #[arg]
+    counts: Vec<u32>,
+}
+
+#[derive(Default)]
+struct TypedArgs;
+
+impl Builtin for TypedArgs {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        let opt: Opt = args.parse()?;
+
+        let stdout_handle = io::stdout();
+        let mut output = BufWriter::new(stdout_handle.lock());
+
+        writeln!(&mut output, "{:?}", opt)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+//! Bash builtin to read shell variables as numbers.
+
+use bash_builtins::{builtin, variables, Result};
+
+/// Print the values of the shell variables NAMEs, as integers.
+#[builtin(name = "typedvars")]
+fn typedvars(
+    #[opt(short = 'd', help = "Value for unset variables.")] default: Option<i64>,
+    names: Vec<String>,
+) -> Result<()> {
+    for name in names {
+        let value = match default {
+            Some(default) => variables::get_or(&name, default),
+            None => variables::get::<i64>(&name),
+        };
+
+        match value {
+            Ok(value) => println!("{} = {}", name, value),
+            Err(e) => println!("{}: {}", name, e),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,24 @@
+//! Bash builtin defined with the `#[builtin]` attribute.
+
+use bash_builtins::{builtin, Result};
+use std::io::{self, BufWriter, Write};
+
+/// Print the uppercase equivalent of the arguments.
+#[builtin(name = "upcasefn")]
+fn upcase(
+    #[opt(short = 'v', help = "Print the original word after each one.")] verbose: bool,
+    words: Vec<String>,
+) -> Result<()> {
+    let stdout_handle = io::stdout();
+    let mut output = BufWriter::new(stdout_handle.lock());
+
+    for word in words {
+        if verbose {
+            writeln!(&mut output, "{}\t{}", word.to_uppercase(), word)?;
+        } else {
+            writeln!(&mut output, "{}", word.to_uppercase())?;
+        }
+    }
+
+    Ok(())
+}
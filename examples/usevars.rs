@@ -10,6 +10,7 @@ builtin_metadata!(name = "usevars", create = UseVars::default);
 struct UseVars;
 
 impl Builtin for UseVars {
+    #[allow(clippy::collapsible_match)]
     fn call(&mut self, args: &mut Args) -> Result<()> {
         let stdout_handle = io::stdout();
         let mut output = BufWriter::new(stdout_handle.lock());
@@ -0,0 +1,62 @@
+//! Bash builtin to set the attributes of shell variables.
+
+use bash_builtins::{builtin, variables, Result};
+
+/// Set the VALUE and the attributes of the variable NAME.
+///
+/// With -p, print the attributes of NAME.
+#[builtin(name = "varattrs")]
+#[allow(clippy::too_many_arguments)]
+fn varattrs(
+    #[opt('p', help = "Print the attributes of the variable.")] print: bool,
+    #[opt('r', help = "Make the variable readonly.")] readonly: bool,
+    #[opt('x', help = "Export the variable.")] export: bool,
+    #[opt('i', help = "Evaluate values as arithmetic expressions.")] integer: bool,
+    #[opt('l', conflicts_with = 'u', help = "Convert values to lowercase.")] lowercase: bool,
+    #[opt('u', help = "Convert values to uppercase.")] uppercase: bool,
+    #[opt('n', help = "Make the variable a reference to another one.")] nameref: bool,
+    name: String,
+    value: Option<String>,
+) -> Result<()> {
+    if print {
+        match variables::find_attributes(&name) {
+            Some(attributes) => println!("{}: {:?}", name, attributes),
+            None => println!("{}: not found", name),
+        }
+
+        return Ok(());
+    }
+
+    let mut declare = variables::declare(&name);
+
+    if readonly {
+        declare = declare.readonly();
+    }
+
+    if export {
+        declare = declare.export();
+    }
+
+    if integer {
+        declare = declare.integer();
+    }
+
+    if lowercase {
+        declare = declare.lowercase();
+    }
+
+    if uppercase {
+        declare = declare.uppercase();
+    }
+
+    if nameref {
+        declare = declare.nameref();
+    }
+
+    if let Some(value) = value {
+        declare = declare.value(value);
+    }
+
+    declare.apply()?;
+    Ok(())
+}
@@ -0,0 +1,22 @@
+//! Bash builtin to print the flags of its arguments.
+
+use bash_builtins::{builtin_metadata, Args, Builtin, Result};
+use std::io::{self, BufWriter, Write};
+
+builtin_metadata!(name = "words", create = Words::default);
+
+#[derive(Default)]
+struct Words;
+
+impl Builtin for Words {
+    fn call(&mut self, args: &mut Args) -> Result<()> {
+        let stdout_handle = io::stdout();
+        let mut output = BufWriter::new(stdout_handle.lock());
+
+        for word in args.words() {
+            writeln!(&mut output, "{:?}: {:?}", word.as_cstr(), word.flags())?;
+        }
+
+        Ok(())
+    }
+}
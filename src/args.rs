@@ -1,8 +1,11 @@
 //! Module to implement the arguments processor.
 
+use crate::convert::FromWordPointer;
+use crate::doc::CommandDoc;
+use crate::words::Word;
+use crate::Assignment;
 use crate::{ffi, Error};
-use std::ffi::CStr;
-use std::marker::PhantomData;
+use std::ffi::{CStr, CString};
 use std::mem;
 use std::os::raw::c_int;
 use std::str::Utf8Error;
@@ -18,6 +21,10 @@ use std::str::Utf8Error;
 /// function provided by bash, like most builtins, and extract them as values
 /// of a type implementing [`BuiltinOptions`].
 ///
+/// The [`parse`] method uses the same parser, but all the options are
+/// collected in a single value, from a struct with the [`BuiltinOptions`]
+/// derive macro.
+///
 /// If the builtin does not expect any option, call to [`no_options`] before
 /// doing anything else.
 ///
@@ -87,12 +94,21 @@ use std::str::Utf8Error;
 /// [`finished`]: Args::finished
 /// [`no_options`]: Args::no_options
 /// [`options`]: Args::options
+/// [`parse`]: Args::parse
 /// [`path_arguments`]: Args::path_arguments
 /// [`raw_arguments`]: Args::raw_arguments
 /// [`string_arguments`]: Args::string_arguments
 pub struct Args {
     word_list: *const ffi::WordList,
     reset_pending: bool,
+
+    /// Name used to invoke the builtin, set by the code generated by
+    /// `builtin_metadata!()`.
+    name: Option<&'static str>,
+
+    /// Subcommand selected before the one selected by `subcommand`. It is
+    /// restored when this instance is dropped.
+    previous_command: Option<Option<&'static CommandDoc>>,
 }
 
 impl Args {
@@ -117,6 +133,55 @@ impl Args {
         Args {
             word_list,
             reset_pending: true,
+            name: None,
+            previous_command: None,
+        }
+    }
+
+    /// Set the name used to invoke the builtin.
+    #[doc(hidden)]
+    pub fn set_builtin_name(&mut self, name: &'static str) {
+        self.name = Some(name);
+    }
+
+    /// Returns the name used to invoke the builtin.
+    ///
+    /// When `builtin_metadata!()` is used with multiple `names`, all of them
+    /// share the same [`Builtin`] instance, and this method can be used to
+    /// know which one was typed in the command-line.
+    ///
+    /// ```
+    /// use bash_builtins::{Args, Builtin, Result};
+    ///
+    /// struct KeyValue;
+    ///
+    /// impl Builtin for KeyValue {
+    ///     fn call(&mut self, args: &mut Args) -> Result<()> {
+    ///         match args.builtin_name() {
+    ///             "kvget" => { /* … */ }
+    ///             "kvset" => { /* … */ }
+    ///             _ => (),
+    ///         }
+    ///
+    ///         Ok(())
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`Builtin`]: crate::Builtin
+    pub fn builtin_name(&self) -> &str {
+        match self.name {
+            Some(name) => name,
+
+            None => unsafe {
+                if ffi::this_command_name.is_null() {
+                    ""
+                } else {
+                    CStr::from_ptr(ffi::this_command_name)
+                        .to_str()
+                        .unwrap_or_default()
+                }
+            },
         }
     }
 
@@ -141,11 +206,118 @@ impl Args {
     where
         T: crate::BuiltinOptions<'a> + 'a,
     {
-        self.ensure_reset();
-        OptionsIterator {
-            args: self,
-            phantom: PhantomData,
+        RawOptions::new(self, T::options(), T::long_options())
+            .map(|opt| opt.and_then(|(opt, arg)| T::from_option(opt, arg)))
+    }
+
+    /// Parses the options in the command-line arguments, and returns a value
+    /// of type `T` with all of them.
+    ///
+    /// The type `T` is a struct with the [`BuiltinOptions`] derive macro.
+    /// See its documentation for details on how to create the parser.
+    ///
+    /// Like with [`options`], free arguments can be read after this method.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bash_builtins::{Args, Builtin, BuiltinOptions, Result};
+    ///
+    /// # struct SomeName;
+    /// #[derive(BuiltinOptions)]
+    /// struct Opt {
+    ///     #[opt = 'f']
+    ///     foo: bool,
+    ///
+    ///     #[opt = 'b']
+    ///     bar: Option<i64>,
+    /// }
+    ///
+    /// impl Builtin for SomeName {
+    ///     fn call(&mut self, args: &mut Args) -> Result<()> {
+    ///         let opt: Opt = args.parse()?;
+    ///
+    ///         if opt.foo {
+    ///             // …
+    ///         }
+    ///
+    ///         Ok(())
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`BuiltinOptions`]: derive.BuiltinOptions.html
+    /// [`options`]: Args::options
+    pub fn parse<'a, T>(&'a mut self) -> crate::Result<T>
+    where
+        T: crate::FromArgs<'a>,
+    {
+        T::from_args(self)
+    }
+
+    /// Returns an iterator to parse the command-line arguments with the
+    /// options in the `options` string and the `long_options` table.
+    ///
+    /// This method is used by the code generated by the [`BuiltinOptions`]
+    /// derive macro.
+    ///
+    /// [`BuiltinOptions`]: derive.BuiltinOptions.html
+    #[doc(hidden)]
+    pub fn raw_options(
+        &mut self,
+        options: &'static [u8],
+        long_options: &'static [LongOption],
+    ) -> RawOptions<'_> {
+        RawOptions::new(self, options, long_options)
+    }
+
+    /// Take the first argument as the name of a subcommand, and returns its
+    /// position in `commands`.
+    ///
+    /// The remaining arguments are available for the parser of the subcommand,
+    /// which will start with a new `internal_getopt` state.
+    ///
+    /// This method is used by the code generated by the [`BuiltinCommands`]
+    /// derive macro.
+    ///
+    /// [`BuiltinCommands`]: derive.BuiltinCommands.html
+    #[doc(hidden)]
+    pub fn subcommand(&mut self, commands: &'static [CommandDoc]) -> crate::Result<usize> {
+        let name = match self.raw_arguments().next() {
+            Some(name) => name,
+
+            None => {
+                crate::log::error("missing command");
+                crate::log::show_usage();
+                return Err(Error::Usage);
+            }
+        };
+
+        if name.to_bytes() == b"--help" {
+            crate::log::show_help();
+            return Err(Error::Usage);
         }
+
+        let index = match commands
+            .iter()
+            .position(|c| c.name.as_bytes() == name.to_bytes())
+        {
+            Some(index) => index,
+
+            None => {
+                crate::error!("{}: invalid command", name.to_string_lossy());
+                crate::log::show_usage();
+                return Err(Error::Usage);
+            }
+        };
+
+        // `internal_getopt` has to start again with the next word.
+        self.reset_pending = true;
+
+        let previous = crate::doc::set_current_command(Some(&commands[index]));
+        self.previous_command.get_or_insert(previous);
+
+        Ok(index)
     }
 
     /// Returns an iterator to get the arguments passed to the builtin.
@@ -228,6 +400,78 @@ impl Args {
             .map(|a| std::str::from_utf8(a.to_bytes()))
     }
 
+    /// Like [`raw_arguments`], but each item is a [`Word`], which includes the
+    /// flags set by bash when the argument was parsed and expanded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bash_builtins::{warning, Args, Builtin, Result};
+    ///
+    /// struct SomeName;
+    ///
+    /// impl Builtin for SomeName {
+    ///     fn call(&mut self, args: &mut Args) -> Result<()> {
+    ///         for word in args.words() {
+    ///             let text = word.to_str()?;
+    ///             if !word.is_quoted() && text.contains('*') {
+    ///                 warning!("{}: unquoted pattern", text);
+    ///             }
+    ///         }
+    ///
+    ///         Ok(())
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`raw_arguments`]: Args::raw_arguments
+    pub fn words(&mut self) -> impl Iterator<Item = Word<'_>> {
+        self.ensure_reset();
+
+        let mut iter = WordListIterator(self);
+        std::iter::from_fn(move || {
+            iter.next_desc()
+                .map(|desc| unsafe { Word::from_desc(desc) })
+        })
+    }
+
+    /// Like [`raw_arguments`], but each item is parsed as an [`Assignment`],
+    /// like the arguments of `declare` or `export`.
+    ///
+    /// Words like `name=value`, `name+=value`, `name[index]=value`, and `name`
+    /// are accepted. If the name is not a valid identifier, the item is an
+    /// [`InvalidAssignment`](crate::InvalidAssignment) error.
+    ///
+    /// Builtins using this method should set `assignment = true` in the
+    /// [`builtin_metadata!()`] macro, so bash expands the words like in the
+    /// `declare` builtin.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bash_builtins::{Args, Builtin, Result};
+    ///
+    /// struct SomeName;
+    ///
+    /// impl Builtin for SomeName {
+    ///     fn call(&mut self, args: &mut Args) -> Result<()> {
+    ///         for assignment in args.assignments() {
+    ///             assignment?.assign()?;
+    ///         }
+    ///
+    ///         Ok(())
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`builtin_metadata!()`]: crate::builtin_metadata
+    /// [`raw_arguments`]: Args::raw_arguments
+    pub fn assignments(
+        &mut self,
+    ) -> impl Iterator<Item = Result<Assignment<'_>, crate::InvalidAssignment>> {
+        self.raw_arguments().map(Assignment::parse)
+    }
+
     /// Returns an error if there are more arguments to be processed.
     ///
     /// If the builtin accepts options but no free arguments, then this method
@@ -326,23 +570,38 @@ impl Args {
     }
 }
 
-struct WordListIterator<'a>(&'a mut Args);
+impl Drop for Args {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous_command.take() {
+            crate::doc::set_current_command(previous);
+        }
+    }
+}
 
-impl<'a> Iterator for WordListIterator<'a> {
-    type Item = &'a CStr;
+struct WordListIterator<'a>(&'a mut Args);
 
-    fn next(&mut self) -> Option<Self::Item> {
+impl<'a> WordListIterator<'a> {
+    /// Returns the next `WordDesc`, and moves the list of `Args` to the next
+    /// word.
+    fn next_desc(&mut self) -> Option<&'a ffi::WordDesc> {
         if self.0.word_list.is_null() {
             return None;
         }
 
-        let word = unsafe {
+        unsafe {
             let current = &*self.0.word_list;
             self.0.word_list = current.next;
-            CStr::from_ptr((*current.word).word)
-        };
+            Some(&*current.word)
+        }
+    }
+}
 
-        Some(word)
+impl<'a> Iterator for WordListIterator<'a> {
+    type Item = &'a CStr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_desc()
+            .map(|desc| unsafe { CStr::from_ptr(desc.word) })
     }
 }
 
@@ -351,20 +610,81 @@ impl<'a> Iterator for WordListIterator<'a> {
 pub trait BuiltinOptions<'a>: Sized {
     fn options() -> &'static [u8];
 
+    fn long_options() -> &'static [LongOption] {
+        &[]
+    }
+
     fn from_option(opt: c_int, arg: Option<&'a CStr>) -> crate::Result<Self>;
 }
 
-struct OptionsIterator<'a, T> {
+/// Trait implemented by the `BuiltinOptions` derive macro for structs.
+#[doc(hidden)]
+pub trait FromArgs<'a>: Sized {
+    fn from_args(args: &'a mut Args) -> crate::Result<Self>;
+}
+
+/// Flag added to the value returned by `internal_getopt` when the option is
+/// used as `+x`.
+///
+/// The same value is used in the code generated by the `BuiltinOptions`
+/// derive macro.
+const PLUS_OPTION: c_int = 0x1_0000;
+
+/// Long option (`--name`) accepted by a `BuiltinOptions` implementation.
+#[doc(hidden)]
+pub struct LongOption {
+    /// Name of the option, without the `--` prefix.
+    pub name: &'static str,
+
+    /// Value sent to `BuiltinOptions::from_option`.
+    pub opt: c_int,
+
+    /// `0` if the option has no argument. Otherwise, the same character used
+    /// in the string for `internal_getopt`.
+    pub argument: u8,
+}
+
+/// Iterator to parse options with `internal_getopt`.
+///
+/// Each item is the value returned by `internal_getopt` (or the `opt` field of
+/// a [`LongOption`]) and its argument, if any.
+#[doc(hidden)]
+pub struct RawOptions<'a> {
     args: &'a mut Args,
-    phantom: PhantomData<T>,
+
+    /// String for `internal_getopt`.
+    options: &'static [u8],
+
+    /// Options that can be used as `--name`.
+    long_options: &'static [LongOption],
+
+    /// Word list sent to `internal_getopt`.
+    list: *const ffi::WordList,
+
+    /// Next word to be processed by `internal_getopt`.
+    current: *const ffi::WordList,
+
+    /// `true` if `internal_getopt` is in the middle of a word, like after
+    /// reading `-a` in `-abc`.
+    in_word: bool,
 }
 
-impl<'a, T: BuiltinOptions<'a>> Iterator for OptionsIterator<'a, T> {
-    type Item = crate::Result<T>;
+impl<'a> Iterator for RawOptions<'a> {
+    type Item = crate::Result<(c_int, Option<&'a CStr>)>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let opt =
-            unsafe { ffi::internal_getopt(self.args.word_list, T::options().as_ptr().cast()) };
+        if let Some(opt) = unsafe { self.long_option() } {
+            return Some(opt);
+        }
+
+        let opt = unsafe { ffi::internal_getopt(self.list, self.options.as_ptr().cast()) };
+
+        // `internal_getopt` moves `lcurrent` to the next word when all
+        // characters of the current one are processed.
+        unsafe {
+            self.in_word = !self.current.is_null() && ffi::lcurrent == self.current;
+            self.current = ffi::lcurrent;
+        }
 
         match opt {
             ffi::GETOPT_EOF => {
@@ -377,12 +697,142 @@ impl<'a, T: BuiltinOptions<'a>> Iterator for OptionsIterator<'a, T> {
                 Some(Err(Error::Usage))
             }
 
-            _ => Some(T::from_option(opt, unsafe { Self::optarg() })),
+            ffi::GETOPT_INVALID => {
+                crate::log::show_usage();
+                Some(Err(Error::Usage))
+            }
+
+            _ => {
+                let opt = if unsafe { ffi::list_opttype } == b'+' as c_int {
+                    opt | PLUS_OPTION
+                } else {
+                    opt
+                };
+
+                Some(Ok((opt, unsafe { Self::optarg() })))
+            }
         }
     }
 }
 
-impl<'a, T> OptionsIterator<'a, T> {
+impl<'a> RawOptions<'a> {
+    fn new(
+        args: &'a mut Args,
+        options: &'static [u8],
+        long_options: &'static [LongOption],
+    ) -> Self {
+        args.ensure_reset();
+        RawOptions {
+            list: args.word_list,
+            current: args.word_list,
+            in_word: false,
+            args,
+            options,
+            long_options,
+        }
+    }
+
+    /// Parse the next word if it is a long option (`--name` or
+    /// `--name=value`).
+    ///
+    /// Long options are not supported by `internal_getopt`, so they are
+    /// processed before it. After a long option, `internal_getopt` is invoked
+    /// with the list starting at the next word, which resets its state.
+    unsafe fn long_option(&mut self) -> Option<crate::Result<(c_int, Option<&'a CStr>)>> {
+        if self.long_options.is_empty() || self.in_word || self.current.is_null() {
+            return None;
+        }
+
+        let word = CStr::from_ptr((*(*self.current).word).word);
+
+        // `--` and `--help` are handled by `internal_getopt`.
+        let name = match word.to_bytes().strip_prefix(b"--") {
+            Some(name) if !name.is_empty() && name != b"help" => name,
+            _ => return None,
+        };
+
+        let (name, mut value) = match name.iter().position(|&c| c == b'=') {
+            Some(eq) => {
+                let value = CStr::from_ptr(word.as_ptr().add(eq + 3));
+                (&name[..eq], Some(value))
+            }
+
+            None => (name, None),
+        };
+
+        let mut next = (*self.current).next;
+
+        let result = match self.long_options.iter().find(|o| o.name.as_bytes() == name) {
+            Some(long) => {
+                // Required arguments can be in the next word.
+                if long.argument == b':' && value.is_none() && !next.is_null() {
+                    value = Some(CStr::from_ptr((*(*next).word).word));
+                    next = (*next).next;
+                }
+
+                match (long.argument, value) {
+                    (0, Some(_)) => {
+                        crate::error!("--{}: option does not accept an argument", long.name);
+                        crate::log::show_usage();
+                        Err(Error::Usage)
+                    }
+
+                    (b':', None) => {
+                        let msg = CString::new(format!("--{}", long.name)).unwrap_or_default();
+                        ffi::sh_needarg(msg.as_ptr());
+                        crate::log::show_usage();
+                        Err(Error::Usage)
+                    }
+
+                    (_, value) => Ok((long.opt, value)),
+                }
+            }
+
+            // `--help=...` is not handled by `internal_getopt`.
+            None if name == b"help" => {
+                crate::error!("--help: option does not accept an argument");
+                crate::log::show_usage();
+                Err(Error::Usage)
+            }
+
+            None => {
+                let mut msg = b"--".to_vec();
+                msg.extend_from_slice(name);
+                if let Ok(msg) = CString::new(msg) {
+                    ffi::sh_invalidopt(msg.as_ptr());
+                }
+
+                crate::log::show_usage();
+                Err(Error::Usage)
+            }
+        };
+
+        self.list = next;
+        self.current = next;
+
+        Some(result)
+    }
+
+    /// Returns the arguments after the last parsed option.
+    ///
+    /// It should be called after the iteration is finished.
+    pub fn arguments(self) -> RawArguments<'a> {
+        RawArguments(WordListIterator(self.args))
+    }
+
+    /// Returns an error if there are arguments after the last parsed option,
+    /// and the options are parsed for a subcommand.
+    ///
+    /// It is used by parsers without positional arguments. The arguments of a
+    /// subcommand can't be read after its parser, so they would be ignored.
+    pub fn finished_in_subcommand(self) -> crate::Result<()> {
+        if self.args.previous_command.is_some() {
+            self.arguments().finished()
+        } else {
+            Ok(())
+        }
+    }
+
     unsafe fn optarg() -> Option<&'a CStr> {
         let optarg = ffi::list_optarg;
         if optarg.is_null() {
@@ -393,6 +843,51 @@ impl<'a, T> OptionsIterator<'a, T> {
     }
 }
 
+/// Positional arguments, converted with [`FromWordPointer`].
+///
+/// The values are taken from the [`Args`] instance, so any argument not
+/// consumed here is still available after the parser.
+///
+/// [`FromWordPointer`]: crate::convert::FromWordPointer
+#[doc(hidden)]
+pub struct RawArguments<'a>(WordListIterator<'a>);
+
+impl<'a> RawArguments<'a> {
+    /// Convert the next argument. It is an error if there are no more
+    /// arguments.
+    pub fn required<T: FromWordPointer<'a>>(&mut self) -> crate::Result<T> {
+        match self.0.next() {
+            Some(arg) => T::extract_value(Some(arg)),
+
+            None => {
+                crate::log::error("too few arguments");
+                Err(Error::Usage)
+            }
+        }
+    }
+
+    /// Convert the next argument, if any.
+    pub fn optional<T: FromWordPointer<'a>>(&mut self) -> crate::Result<Option<T>> {
+        self.0
+            .next()
+            .map(|arg| T::extract_value(Some(arg)))
+            .transpose()
+    }
+
+    /// Convert all remaining arguments.
+    pub fn remaining<T: FromWordPointer<'a>>(&mut self) -> crate::Result<Vec<T>> {
+        self.0
+            .by_ref()
+            .map(|arg| T::extract_value(Some(arg)))
+            .collect()
+    }
+
+    /// Returns an error if there are more arguments.
+    pub fn finished(self) -> crate::Result<()> {
+        (self.0).0.finished()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,159 @@
+//! Evaluation of arithmetic expressions.
+//!
+//! The expressions are evaluated by bash, so they accept the same syntax of
+//! `let` and `(( … ))`, including shell variables (like `x * 2`) and
+//! assignments (like `x += 1`). Like in `let`, the expression is not expanded
+//! again, so `$x` is a syntax error.
+//!
+//! Syntax errors are reported by bash, with a message like `x *: syntax error:
+//! operand expected`.
+//!
+//! Unset variables are evaluated as `0`, even if `set -u` is enabled.
+//!
+//! [`Arith`] can be used in [`BuiltinOptions`] to accept arithmetic
+//! expressions in the arguments of a builtin.
+//!
+//! [`BuiltinOptions`]: bash_builtins_macro::BuiltinOptions
+
+use crate::convert::FromWordPointer;
+use crate::ffi;
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::ops::Deref;
+use std::os::raw::c_int;
+
+/// Flag for `evalexp` to indicate that the expression is already expanded.
+const EXP_EXPANDED: c_int = 0x01;
+
+/// Evaluate an arithmetic expression.
+///
+/// If the expression is not valid, bash prints an error, and this function
+/// returns [`Error::ExitCode`](crate::Error::ExitCode), so the builtin
+/// returns with the same exit status of `let`.
+///
+/// # Example
+///
+/// ```no_run
+/// use bash_builtins::{arith, variables};
+///
+/// # fn f() -> bash_builtins::Result<()> {
+/// variables::set("x", "10")?;
+/// assert_eq!(arith::eval("x * 2 + 1")?, 21);
+/// # Ok(())
+/// # }
+/// ```
+pub fn eval(expr: &str) -> crate::Result<i64> {
+    let expr = CString::new(expr)?;
+
+    match evaluate(&expr) {
+        Some(n) => Ok(n),
+        None => Err(crate::Error::ExitCode(ffi::exit::EXECUTION_FAILURE)),
+    }
+}
+
+pub(crate) fn evaluate(expr: &CStr) -> Option<i64> {
+    let mut valid = 0;
+
+    // With `set -u`, bash aborts the command with a `longjmp` when it finds
+    // an unset variable, which would skip the Rust frames. The option is
+    // disabled during the evaluation to avoid it.
+    let result = unsafe {
+        let unbound_vars_is_error = ffi::unbound_vars_is_error;
+        ffi::unbound_vars_is_error = 0;
+
+        let result = ffi::evalexp(expr.as_ptr(), EXP_EXPANDED, &mut valid);
+
+        ffi::unbound_vars_is_error = unbound_vars_is_error;
+        result
+    };
+
+    if valid == 0 {
+        None
+    } else {
+        Some(result as i64)
+    }
+}
+
+/// An argument evaluated as an arithmetic expression.
+///
+/// The value is computed as an `i64`, and then converted to `T`.
+///
+/// # Example
+///
+/// ```
+/// # use bash_builtins::BuiltinOptions;
+/// use bash_builtins::arith::Arith;
+///
+/// #[derive(BuiltinOptions)]
+/// struct Opt {
+///     #[opt = 'n']
+///     count: Option<Arith<u32>>,
+/// }
+/// ```
+///
+/// With this parser, both `-n $((x * 2))` and `-n 'x * 2'` are accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Arith<T>(pub T);
+
+impl<T> Arith<T> {
+    /// Returns the evaluated value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Arith<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Arith<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+/// Error returned by the [`FromWordPointer`] implementation of [`Arith`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum ArithError {
+    /// The expression is not valid. The error is already printed by bash.
+    InvalidExpression,
+
+    /// The result can't be converted to the target type.
+    OutOfRange(i64),
+}
+
+impl fmt::Display for ArithError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArithError::InvalidExpression => fmt.write_str("invalid arithmetic expression"),
+            ArithError::OutOfRange(n) => write!(fmt, "{}: value out of range", n),
+        }
+    }
+}
+
+impl std::error::Error for ArithError {}
+
+impl<'a, T> FromWordPointer<'a> for Arith<T>
+where
+    T: TryFrom<i64> + 'a,
+{
+    type Err = ArithError;
+
+    fn from_cstr(s: &'a CStr) -> Result<Self, Self::Err> {
+        let n = evaluate(s).ok_or(ArithError::InvalidExpression)?;
+        T::try_from(n)
+            .map(Arith)
+            .map_err(|_| ArithError::OutOfRange(n))
+    }
+
+    fn print_error(arg: &CStr, error: &Self::Err) {
+        if *error != ArithError::InvalidExpression {
+            crate::error!("{:?}: {}", arg, error);
+        }
+    }
+}
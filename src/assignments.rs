@@ -0,0 +1,219 @@
+//! Arguments of assignment builtins, like `name=value`.
+
+use crate::variables::{self, Attributes, VariableError};
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString};
+use std::fmt;
+
+/// An argument like `name=value`, `name+=value`, or `name[index]=value`.
+///
+/// Items of the [`Args::assignments`](crate::Args::assignments) iterator.
+///
+/// Words without `=` are also accepted, like in `declare name`. In that case,
+/// [`value`](Assignment::value) returns `None`.
+#[derive(Debug, Clone, Copy)]
+pub struct Assignment<'a> {
+    name: &'a str,
+    index: Option<&'a [u8]>,
+    value: Option<&'a CStr>,
+    append: bool,
+}
+
+impl<'a> Assignment<'a> {
+    /// Parse a word. The name is validated with the `legal_identifier`
+    /// function from bash.
+    pub fn parse(word: &'a CStr) -> Result<Self, InvalidAssignment> {
+        let invalid = || InvalidAssignment(word.to_string_lossy().into_owned());
+
+        let bytes = word.to_bytes_with_nul();
+        let name_len = bytes
+            .iter()
+            .position(|&c| !(c.is_ascii_alphanumeric() || c == b'_'))
+            .unwrap_or(0);
+
+        let name = std::str::from_utf8(&bytes[..name_len]).map_err(|_| invalid())?;
+        let mut rest = &bytes[name_len..];
+
+        let is_legal = CString::new(name)
+            .map(|n| unsafe { crate::ffi::variables::legal_identifier(n.as_ptr()) != 0 })
+            .unwrap_or(false);
+
+        if !is_legal {
+            return Err(invalid());
+        }
+
+        // Subscript. It can contain nested brackets, like `a[b[1]]`.
+        let mut index = None;
+        if rest.first() == Some(&b'[') {
+            let mut depth = 0;
+            let end = rest.iter().position(|&c| {
+                match c {
+                    b'[' => depth += 1,
+                    b']' => depth -= 1,
+                    _ => (),
+                }
+
+                depth == 0
+            });
+
+            match end {
+                Some(end) => {
+                    index = Some(&rest[1..end]);
+                    rest = &rest[end + 1..];
+                }
+
+                None => return Err(invalid()),
+            }
+        }
+
+        let (append, value) = match rest {
+            [0] => (false, None),
+            [b'=', ..] => (false, Some(&rest[1..])),
+            [b'+', b'=', ..] => (true, Some(&rest[2..])),
+            _ => return Err(invalid()),
+        };
+
+        let value = value.map(|v| CStr::from_bytes_with_nul(v).map_err(|_| invalid()));
+
+        Ok(Assignment {
+            name,
+            index,
+            value: value.transpose()?,
+            append,
+        })
+    }
+
+    /// Name of the variable.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Subscript between brackets, if any.
+    pub fn index(&self) -> Option<&'a [u8]> {
+        self.index
+    }
+
+    /// Value after the `=`, if any.
+    pub fn value(&self) -> Option<&'a CStr> {
+        self.value
+    }
+
+    /// Returns `true` if the operator is `+=`.
+    pub fn is_append(&self) -> bool {
+        self.append
+    }
+
+    /// Assign the value to the shell variable.
+    ///
+    /// The value is written with [`variables::set`], [`variables::array_set`],
+    /// or [`variables::assoc_set`]:
+    ///
+    /// * Without a subscript, the variable is set to the value. If the
+    ///   variable is an indexed array, the element at `0` is modified, like in
+    ///   bash.
+    /// * If the variable is an associative array, the subscript is the key.
+    /// * Otherwise, the subscript is evaluated as an arithmetic expression,
+    ///   and the result is the index in the array. Negative indices count back
+    ///   from the end of the array.
+    ///
+    /// With `+=`, the value is appended to the current one. If the variable has
+    /// the `integer` attribute, the value is evaluated and added to the current
+    /// one, like in bash.
+    ///
+    /// If there is no value (like in `declare name`), the variable is not
+    /// modified.
+    pub fn assign(&self) -> Result<(), VariableError> {
+        let value = match self.value {
+            Some(value) => value.to_bytes(),
+            None => return Ok(()),
+        };
+
+        let var = variables::find_raw(self.name);
+        let (is_array, is_assoc, is_integer) = match &var {
+            Some(var) => unsafe {
+                let is_integer = var.attributes().contains(Attributes::INTEGER);
+                (var.is_array(), var.is_assoc(), is_integer)
+            },
+            None => (false, false, false),
+        };
+
+        let concat = |current: Option<CString>| {
+            let current = match current {
+                Some(current) if self.append && !current.as_bytes().is_empty() => current,
+                _ => return value.to_owned(),
+            };
+
+            // The expression is evaluated when the value is assigned to the
+            // integer variable.
+            let mut new = current.into_bytes();
+            if is_integer {
+                new.extend_from_slice(b" + (");
+                new.extend_from_slice(value);
+                new.push(b')');
+            } else {
+                new.extend_from_slice(value);
+            }
+
+            new
+        };
+
+        match self.index {
+            Some(key) if is_assoc => {
+                let new = concat(variables::assoc_get(self.name, key));
+                variables::assoc_set(self.name, key, new)
+            }
+
+            None if !is_array => {
+                let new = concat(variables::find_as_string(self.name));
+                variables::set(self.name, new)
+            }
+
+            index => {
+                let index = match index {
+                    Some(index) => self.array_index(index)?,
+                    None => 0,
+                };
+
+                let new = concat(variables::array_get(self.name, index));
+                variables::array_set(self.name, index, new)
+            }
+        }
+    }
+
+    /// Evaluate the subscript of an indexed array.
+    fn array_index(&self, index: &[u8]) -> Result<usize, VariableError> {
+        let expr = std::str::from_utf8(index).map_err(|_| VariableError::InvalidIndex)?;
+        let index = crate::arith::eval(expr).map_err(|_| VariableError::InvalidIndex)?;
+
+        if index >= 0 {
+            return Ok(index as usize);
+        }
+
+        // Negative indices are relative to the end of the array.
+        let last = variables::find_raw(self.name)
+            .filter(|var| unsafe { var.is_array() })
+            .and_then(|var| unsafe { var.array_items() }.last().map(|(i, _)| *i))
+            .ok_or(VariableError::InvalidIndex)?;
+
+        usize::try_from(last + 1 + index).map_err(|_| VariableError::InvalidIndex)
+    }
+}
+
+/// Error returned when a word is not a valid assignment.
+#[derive(Debug)]
+pub struct InvalidAssignment(String);
+
+impl InvalidAssignment {
+    /// The word found in the command-line.
+    pub fn word(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for InvalidAssignment {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "`{}': not a valid identifier", self.0)
+    }
+}
+
+impl std::error::Error for InvalidAssignment {}
@@ -0,0 +1,59 @@
+//! Types for bit flags defined by bash, like [`WordFlags`](crate::WordFlags).
+
+use std::fmt;
+use std::os::raw::c_int;
+
+/// Define the constants of a bit flags type, and implement [`Debug`] for it.
+///
+/// The type must be a tuple struct with a single `c_int` field.
+macro_rules! bit_flags {
+    ($type:ident { $($(#[$attr:meta])* $name:ident = $value:expr;)* }) => {
+        impl $type {
+            $(
+                $(#[$attr])*
+                pub const $name: $type = $type($value);
+            )*
+
+            const NAMES: &'static [(&'static str, ::std::os::raw::c_int)] = &[
+                $((stringify!($name), $value),)*
+            ];
+        }
+
+        impl ::std::fmt::Debug for $type {
+            fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                $crate::bit_flags::debug(fmt, self.0, Self::NAMES)
+            }
+        }
+    };
+}
+
+pub(crate) use bit_flags;
+
+/// Write the names of the flags in `bits`, separated by `|`. Unknown flags
+/// are written as a hexadecimal number.
+pub(crate) fn debug(fmt: &mut fmt::Formatter, bits: c_int, names: &[(&str, c_int)]) -> fmt::Result {
+    let mut rest = bits;
+    let mut first = true;
+
+    for (name, value) in names {
+        if rest & value != 0 {
+            if !first {
+                fmt.write_str(" | ")?;
+            }
+
+            fmt.write_str(name)?;
+            rest &= !value;
+            first = false;
+        }
+    }
+
+    if rest != 0 || first {
+        if !first {
+            fmt.write_str(" | ")?;
+        }
+
+        write!(fmt, "{:#x}", rest)?;
+    }
+
+    Ok(())
+}
@@ -3,12 +3,32 @@
 //! This module implements the trait [`FromWordPointer`] to convert [`CStr`]
 //! instances to another type.
 //!
+//! Besides the types from the standard library, this module provides types
+//! for values that are common in shell builtins: [signals](Signal), [file
+//! descriptors](Fd), [file modes](Mode), [users](User), [groups](Group), and
+//! [byte sizes](ByteSize). [`Duration`](std::time::Duration) values accept
+//! units like `250ms` or `1.5s`.
+//!
 //! [`CStr`]: std::ffi::CStr
 
 use std::ffi::CStr;
 use std::fmt;
 use std::str::{FromStr, Utf8Error};
 
+pub use bash_builtins_macro::FromWordPointer;
+
+mod fd;
+mod mode;
+mod signal;
+mod units;
+mod users;
+
+pub use fd::{Fd, InvalidFd};
+pub use mode::{InvalidMode, Mode};
+pub use signal::{InvalidSignal, Signal};
+pub use units::{ByteSize, InvalidQuantity};
+pub use users::{Group, InvalidId, User};
+
 #[cfg(unix)]
 use std::os::unix::ffi::{OsStrExt, OsStringExt};
 
@@ -32,6 +52,12 @@ pub trait FromWordPointer<'a>: Sized + 'a {
     #[doc(hidden)]
     const OPTSTR_ARGUMENT: u8 = b':';
 
+    /// Valid values for this type, if they are a fixed list.
+    ///
+    /// The list is included in the generated help text.
+    #[doc(hidden)]
+    const CHOICES: &'static [&'static str] = &[];
+
     /// Try to extract the value from a raw argument.
     #[doc(hidden)]
     fn extract_value(arg: Option<&'a CStr>) -> crate::Result<Self> {
@@ -42,17 +68,25 @@ pub trait FromWordPointer<'a>: Sized + 'a {
             }
 
             Some(arg) => Self::from_cstr(arg).map_err(|e| {
-                crate::error!("{:?}: {}", arg, e);
+                Self::print_error(arg, &e);
                 crate::Error::Usage
             }),
         }
     }
+
+    /// Print the error returned by `from_cstr`.
+    #[doc(hidden)]
+    fn print_error(arg: &CStr, error: &Self::Err) {
+        crate::error!("{:?}: {}", arg, error);
+    }
 }
 
 // For non-required arguments.
 impl<'a, T: FromWordPointer<'a>> FromWordPointer<'a> for Option<T> {
     const OPTSTR_ARGUMENT: u8 = b';';
 
+    const CHOICES: &'static [&'static str] = <T as FromWordPointer<'a>>::CHOICES;
+
     type Err = <T as FromWordPointer<'a>>::Err;
 
     fn from_cstr(s: &'a CStr) -> Result<Self, Self::Err> {
@@ -67,12 +101,16 @@ impl<'a, T: FromWordPointer<'a>> FromWordPointer<'a> for Option<T> {
                 Ok(v) => Ok(Some(v)),
 
                 Err(e) => {
-                    crate::error!("{:?}: {}", arg, e);
+                    Self::print_error(arg, &e);
                     Err(crate::Error::Usage)
                 }
             },
         }
     }
+
+    fn print_error(arg: &CStr, error: &Self::Err) {
+        <T as FromWordPointer<'a>>::print_error(arg, error);
+    }
 }
 
 // Standard types.
@@ -129,6 +167,51 @@ impl<'a> FromWordPointer<'a> for OsString {
     }
 }
 
+// Values from a fixed list.
+
+/// Error returned when a value is not one of the valid choices.
+///
+/// It is used by the implementations generated by the [`FromWordPointer`
+/// derive macro](bash_builtins_macro::FromWordPointer).
+#[derive(Debug)]
+pub struct InvalidChoice {
+    value: String,
+    choices: &'static [&'static str],
+}
+
+impl InvalidChoice {
+    #[doc(hidden)]
+    pub fn new(value: &CStr, choices: &'static [&'static str]) -> Self {
+        InvalidChoice {
+            value: value.to_string_lossy().into_owned(),
+            choices,
+        }
+    }
+
+    /// The value found in the command-line.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// The list of valid values.
+    pub fn choices(&self) -> &'static [&'static str] {
+        self.choices
+    }
+}
+
+impl fmt::Display for InvalidChoice {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "invalid value '{}' (expected one of: {})",
+            self.value,
+            self.choices.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for InvalidChoice {}
+
 // Types that have to be converted to a string as an intermediate step.
 
 #[doc(hidden)]
@@ -0,0 +1,58 @@
+//! File descriptor numbers.
+
+use super::FromWordPointer;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::unix::io::{AsRawFd, RawFd};
+
+/// A file descriptor number, like the argument of `read -u`.
+///
+/// The value is only parsed. Use [`is_open`](Fd::is_open) to check if the file
+/// descriptor is open in the shell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fd(RawFd);
+
+impl Fd {
+    /// Returns `true` if the file descriptor is open.
+    pub fn is_open(self) -> bool {
+        unsafe { libc::fcntl(self.0, libc::F_GETFD) != -1 }
+    }
+}
+
+impl AsRawFd for Fd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+impl fmt::Display for Fd {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+/// Error returned when the value is not a valid file descriptor number.
+#[derive(Debug)]
+pub struct InvalidFd;
+
+impl fmt::Display for InvalidFd {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("invalid file descriptor")
+    }
+}
+
+impl std::error::Error for InvalidFd {}
+
+impl<'a> FromWordPointer<'a> for Fd {
+    type Err = InvalidFd;
+
+    fn from_cstr(s: &'a CStr) -> Result<Self, Self::Err> {
+        let s = s.to_str().map_err(|_| InvalidFd)?;
+
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(InvalidFd);
+        }
+
+        s.parse().map(Fd).map_err(|_| InvalidFd)
+    }
+}
@@ -0,0 +1,373 @@
+//! File mode bits.
+
+use super::FromWordPointer;
+use std::ffi::CStr;
+use std::fmt;
+
+const S_ISUID: u32 = 0o4000;
+const S_ISGID: u32 = 0o2000;
+const S_ISVTX: u32 = 0o1000;
+const S_IFDIR: u32 = 0o040000;
+
+const WHO_USER: u32 = S_ISUID | 0o700;
+const WHO_GROUP: u32 = S_ISGID | 0o070;
+const WHO_OTHER: u32 = S_ISVTX | 0o007;
+const WHO_ALL: u32 = WHO_USER | WHO_GROUP | WHO_OTHER;
+
+/// A file mode, as accepted by `chmod` and `umask`.
+///
+/// The mode can be an octal number (`644`), or a comma-separated list of
+/// symbolic clauses (`u+x,go-w`). Each clause is a list of users (`ugoa`),
+/// followed by one or more operators (`+`, `-`, `=`), each one with a list of
+/// permissions (`rwxXst`) or a copy of the permissions of other users (`ugo`).
+/// If the users are omitted, the clause is applied to all of them, except for
+/// the bits in the umask.
+///
+/// Symbolic modes are relative to an existing mode, so the final value is
+/// computed with [`apply_with_umask`](Mode::apply_with_umask), like `chmod`
+/// does. [`apply`](Mode::apply) ignores the umask.
+///
+/// # Example
+///
+/// ```
+/// # use bash_builtins::convert::{FromWordPointer, Mode};
+/// # use std::ffi::CStr;
+/// let mode = Mode::from_cstr(CStr::from_bytes_with_nul(b"u+x,go-w\0").unwrap()).unwrap();
+/// assert_eq!(mode.apply(0o666), 0o744);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mode(ModeKind);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ModeKind {
+    Octal(u32),
+    Symbolic(Vec<Clause>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Clause {
+    /// Bits for the users in the clause. `0` if the users are omitted.
+    who: u32,
+    actions: Vec<Action>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Action {
+    op: char,
+    perms: u32,
+    conditional_exec: bool,
+    copy: Option<char>,
+}
+
+impl Mode {
+    /// Returns the value if the mode was written as an octal number.
+    pub fn octal(&self) -> Option<u32> {
+        match self.0 {
+            ModeKind::Octal(mode) => Some(mode),
+            ModeKind::Symbolic(_) => None,
+        }
+    }
+
+    /// Computes the new mode from the current one.
+    ///
+    /// It is the same as [`apply_with_umask`](Mode::apply_with_umask) with an
+    /// empty umask, so clauses without users change the bits of all of them.
+    pub fn apply(&self, current: u32) -> u32 {
+        self.apply_with_umask(current, 0)
+    }
+
+    /// Computes the new mode from the current one, like `chmod` does with the
+    /// given `umask`.
+    ///
+    /// Bits outside of the permissions (like the file type) are preserved.
+    /// `X` sets the execute bits only if `current` is a directory, or if some
+    /// execute bit is already set.
+    ///
+    /// In clauses without users (like `+w`), the bits in `umask` are not set.
+    /// Octal modes and clauses with users (like `a+w`) ignore the umask.
+    pub fn apply_with_umask(&self, current: u32, umask: u32) -> u32 {
+        let clauses = match &self.0 {
+            ModeKind::Octal(mode) => return (current & !0o7777) | mode,
+            ModeKind::Symbolic(clauses) => clauses,
+        };
+
+        let mut mode = current;
+
+        for clause in clauses {
+            let (who, allowed) = match clause.who {
+                0 => (WHO_ALL, !umask),
+                who => (who, !0),
+            };
+
+            for action in &clause.actions {
+                let mut bits = action.perms;
+
+                if action.conditional_exec && (mode & S_IFDIR != 0 || mode & 0o111 != 0) {
+                    bits |= 0o111;
+                }
+
+                bits |= match action.copy {
+                    Some('u') => ((mode >> 6) & 7) * 0o111,
+                    Some('g') => ((mode >> 3) & 7) * 0o111,
+                    Some('o') => (mode & 7) * 0o111,
+                    _ => 0,
+                };
+
+                bits &= who & allowed;
+
+                mode = match action.op {
+                    '+' => mode | bits,
+                    '-' => mode & !bits,
+                    _ => (mode & !who) | bits,
+                };
+            }
+        }
+
+        mode
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let clauses = match &self.0 {
+            ModeKind::Octal(mode) => return write!(fmt, "{:04o}", mode),
+            ModeKind::Symbolic(clauses) => clauses,
+        };
+
+        for (idx, clause) in clauses.iter().enumerate() {
+            if idx > 0 {
+                fmt.write_str(",")?;
+            }
+
+            if clause.who == WHO_ALL {
+                fmt.write_str("a")?;
+            } else {
+                for (who, c) in [(WHO_USER, 'u'), (WHO_GROUP, 'g'), (WHO_OTHER, 'o')] {
+                    if clause.who & who != 0 {
+                        write!(fmt, "{}", c)?;
+                    }
+                }
+            }
+
+            for action in &clause.actions {
+                write!(fmt, "{}", action.op)?;
+
+                let perms = [
+                    (0o444, 'r'),
+                    (0o222, 'w'),
+                    (0o111, 'x'),
+                    (S_ISUID | S_ISGID, 's'),
+                    (S_ISVTX, 't'),
+                ];
+
+                for (bits, c) in perms {
+                    if action.perms & bits != 0 {
+                        write!(fmt, "{}", c)?;
+                    }
+                }
+
+                if action.conditional_exec {
+                    fmt.write_str("X")?;
+                }
+
+                if let Some(c) = action.copy {
+                    write!(fmt, "{}", c)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Error returned when the value is not a valid mode.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidMode {
+    /// The octal number is greater than `7777`.
+    OctalOutOfRange,
+
+    /// A character that is not an octal digit in an octal number.
+    InvalidOctalDigit(char),
+
+    /// A clause without operator.
+    MissingOperator,
+
+    /// An unknown character in the place of an operator.
+    InvalidOperator(char),
+
+    /// An unknown character in the list of permissions.
+    InvalidCharacter(char),
+}
+
+impl fmt::Display for InvalidMode {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidMode::OctalOutOfRange => fmt.write_str("octal number out of range"),
+            InvalidMode::InvalidOctalDigit(c) => write!(fmt, "invalid octal digit '{}'", c),
+            InvalidMode::MissingOperator => fmt.write_str("missing symbolic mode operator"),
+            InvalidMode::InvalidOperator(c) => {
+                write!(fmt, "invalid symbolic mode operator '{}'", c)
+            }
+            InvalidMode::InvalidCharacter(c) => {
+                write!(fmt, "invalid symbolic mode character '{}'", c)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidMode {}
+
+impl<'a> FromWordPointer<'a> for Mode {
+    type Err = InvalidMode;
+
+    fn from_cstr(s: &'a CStr) -> Result<Self, Self::Err> {
+        let s = s.to_bytes();
+
+        if s.first().map(u8::is_ascii_digit).unwrap_or(false) {
+            return parse_octal(s).map(|m| Mode(ModeKind::Octal(m)));
+        }
+
+        s.split(|&c| c == b',')
+            .map(parse_clause)
+            .collect::<Result<_, _>>()
+            .map(|clauses| Mode(ModeKind::Symbolic(clauses)))
+    }
+}
+
+fn parse_octal(s: &[u8]) -> Result<u32, InvalidMode> {
+    let mut mode = 0u32;
+
+    for &c in s {
+        if !(b'0'..=b'7').contains(&c) {
+            return Err(InvalidMode::InvalidOctalDigit(char::from(c)));
+        }
+
+        mode = mode * 8 + u32::from(c - b'0');
+
+        if mode > 0o7777 {
+            return Err(InvalidMode::OctalOutOfRange);
+        }
+    }
+
+    Ok(mode)
+}
+
+fn parse_clause(s: &[u8]) -> Result<Clause, InvalidMode> {
+    let mut chars = s.iter().map(|&c| char::from(c)).peekable();
+
+    let mut who = 0;
+    while let Some(c) = chars.peek() {
+        who |= match c {
+            'u' => WHO_USER,
+            'g' => WHO_GROUP,
+            'o' => WHO_OTHER,
+            'a' => WHO_ALL,
+            _ => break,
+        };
+
+        chars.next();
+    }
+
+    let mut actions = Vec::new();
+
+    loop {
+        let op = match chars.next() {
+            Some(c @ ('+' | '-' | '=')) => c,
+            Some(c) => return Err(InvalidMode::InvalidOperator(c)),
+            None if actions.is_empty() => return Err(InvalidMode::MissingOperator),
+            None => break,
+        };
+
+        let mut action = Action {
+            op,
+            perms: 0,
+            conditional_exec: false,
+            copy: None,
+        };
+
+        while let Some(&c) = chars.peek() {
+            match c {
+                'r' => action.perms |= 0o444,
+                'w' => action.perms |= 0o222,
+                'x' => action.perms |= 0o111,
+                's' => action.perms |= S_ISUID | S_ISGID,
+                't' => action.perms |= S_ISVTX,
+                'X' => action.conditional_exec = true,
+                'u' | 'g' | 'o' if action.copy.is_none() => action.copy = Some(c),
+                '+' | '-' | '=' => break,
+                _ => return Err(InvalidMode::InvalidCharacter(c)),
+            }
+
+            chars.next();
+        }
+
+        actions.push(action);
+    }
+
+    Ok(Clause { who, actions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(s: &str) -> Result<Mode, InvalidMode> {
+        let s = std::ffi::CString::new(s).unwrap();
+        Mode::from_cstr(&s)
+    }
+
+    #[test]
+    fn octal_modes() {
+        assert_eq!(mode("644").unwrap().octal(), Some(0o644));
+        assert_eq!(mode("0755").unwrap().apply(0o100_600), 0o100_755);
+        assert_eq!(mode("4755").unwrap().to_string(), "4755");
+
+        assert_eq!(mode("8"), Err(InvalidMode::InvalidOctalDigit('8')));
+        assert_eq!(mode("7a"), Err(InvalidMode::InvalidOctalDigit('a')));
+        assert_eq!(mode("17777"), Err(InvalidMode::OctalOutOfRange));
+    }
+
+    #[test]
+    fn symbolic_modes() {
+        let apply = |s, current| mode(s).unwrap().apply(current);
+
+        assert_eq!(apply("u+x", 0o644), 0o744);
+        assert_eq!(apply("go-w", 0o666), 0o644);
+        assert_eq!(apply("a=r", 0o777), 0o444);
+        assert_eq!(apply("=rw", 0o4755), 0o666);
+        assert_eq!(apply("u=rwx,g=rx,o=", 0o000), 0o750);
+        assert_eq!(apply("g=u", 0o740), 0o770);
+        assert_eq!(apply("o+t", 0o777), 0o1777);
+        assert_eq!(apply("u+s-x", 0o755), 0o4655);
+
+        // `X` depends on the current mode.
+        assert_eq!(apply("a+X", 0o644), 0o644);
+        assert_eq!(apply("a+X", 0o744), 0o755);
+        assert_eq!(apply("a+X", S_IFDIR | 0o600), S_IFDIR | 0o711);
+
+        assert_eq!(mode("go-w,u+x").unwrap().to_string(), "go-w,u+x");
+        assert_eq!(mode("a=r,+w").unwrap().to_string(), "a=r,+w");
+        assert_eq!(mode("a+rX").unwrap().octal(), None);
+    }
+
+    #[test]
+    fn umask() {
+        let apply = |s, current| mode(s).unwrap().apply_with_umask(current, 0o022);
+
+        assert_eq!(apply("+w", 0o444), 0o644);
+        assert_eq!(apply("=rwx", 0o000), 0o755);
+        assert_eq!(apply("-w", 0o666), 0o466);
+        assert_eq!(apply("a+w", 0o444), 0o666);
+        assert_eq!(apply("go+w", 0o444), 0o466);
+        assert_eq!(apply("666", 0o000), 0o666);
+    }
+
+    #[test]
+    fn invalid_symbolic_modes() {
+        assert_eq!(mode(""), Err(InvalidMode::MissingOperator));
+        assert_eq!(mode("u"), Err(InvalidMode::MissingOperator));
+        assert_eq!(mode("u+x,"), Err(InvalidMode::MissingOperator));
+        assert_eq!(mode("uz+x"), Err(InvalidMode::InvalidOperator('z')));
+        assert_eq!(mode("u+q"), Err(InvalidMode::InvalidCharacter('q')));
+    }
+}
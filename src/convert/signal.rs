@@ -0,0 +1,84 @@
+//! Signal specifications.
+
+use super::FromWordPointer;
+use crate::ffi;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::c_int;
+
+/// Flags for `decode_signal`.
+const DSIG_SIGPREFIX: c_int = 0x01;
+const DSIG_NOCASE: c_int = 0x02;
+
+/// Value returned by `decode_signal` for invalid specifications.
+const NO_SIG: c_int = -1;
+
+/// A signal, from its name (`INT`, `SIGINT`) or its number (`2`).
+///
+/// Names are decoded with the signal table of bash, like in the `kill` and
+/// `trap` builtins, so the `SIG` prefix is optional, and the case is ignored.
+/// The table includes the pseudo-signals used by `trap` (`EXIT`, `DEBUG`,
+/// `ERR`, and `RETURN`).
+///
+/// # Example
+///
+/// ```
+/// # use bash_builtins::BuiltinOptions;
+/// use bash_builtins::convert::Signal;
+///
+/// #[derive(BuiltinOptions)]
+/// struct Opt {
+///     #[opt = 's']
+///     signal: Option<Signal>,
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Signal(c_int);
+
+impl Signal {
+    /// Signal number.
+    pub fn number(self) -> c_int {
+        self.0
+    }
+
+    /// Signal name, like `SIGINT`, from the table of bash.
+    pub fn name(self) -> String {
+        unsafe {
+            let name = ffi::signal_name(self.0);
+            if name.is_null() {
+                return self.0.to_string();
+            }
+
+            CStr::from_ptr(name).to_string_lossy().into_owned()
+        }
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(&self.name())
+    }
+}
+
+/// Error returned when the value is not a valid signal specification.
+#[derive(Debug)]
+pub struct InvalidSignal;
+
+impl fmt::Display for InvalidSignal {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("invalid signal specification")
+    }
+}
+
+impl std::error::Error for InvalidSignal {}
+
+impl<'a> FromWordPointer<'a> for Signal {
+    type Err = InvalidSignal;
+
+    fn from_cstr(s: &'a CStr) -> Result<Self, Self::Err> {
+        match unsafe { ffi::decode_signal(s.as_ptr(), DSIG_SIGPREFIX | DSIG_NOCASE) } {
+            NO_SIG => Err(InvalidSignal),
+            signal => Ok(Signal(signal)),
+        }
+    }
+}
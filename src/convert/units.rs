@@ -0,0 +1,296 @@
+//! Durations and byte sizes.
+
+use super::{FromWordPointer, Utf8OrParseError};
+use std::convert::{TryFrom, TryInto};
+use std::ffi::CStr;
+use std::fmt;
+use std::time::Duration;
+
+/// Maximum number of digits used in the fractional part of a value.
+const MAX_FRACTION_DIGITS: u32 = 18;
+
+/// Error returned when a duration or a byte size can't be parsed.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidQuantity {
+    /// The value does not start with a decimal number.
+    InvalidNumber,
+
+    /// The suffix after the number is not a known unit.
+    InvalidUnit(String),
+
+    /// The value is too large for the type.
+    Overflow,
+}
+
+impl fmt::Display for InvalidQuantity {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidQuantity::InvalidNumber => fmt.write_str("invalid number"),
+            InvalidQuantity::InvalidUnit(unit) => write!(fmt, "invalid unit '{}'", unit),
+            InvalidQuantity::Overflow => fmt.write_str("value too large"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidQuantity {}
+
+/// Durations, like `1.5s`, `250ms`, or `2h`.
+///
+/// The value is a decimal number, optionally followed by one of these units:
+///
+/// | Unit         | Meaning      |
+/// |--------------|--------------|
+/// | `ns`         | Nanoseconds  |
+/// | `us` or `µs` | Microseconds |
+/// | `ms`         | Milliseconds |
+/// | `s`          | Seconds      |
+/// | `m` or `min` | Minutes      |
+/// | `h`          | Hours        |
+/// | `d`          | Days         |
+///
+/// Values without a unit are seconds, like in `read -t` and `sleep`.
+impl<'a> FromWordPointer<'a> for Duration {
+    type Err = Utf8OrParseError<InvalidQuantity>;
+
+    fn from_cstr(s: &'a CStr) -> Result<Self, Self::Err> {
+        const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+        let s = s.to_str().map_err(Utf8OrParseError::Utf8)?;
+        let (number, unit) = split_unit(s).map_err(Utf8OrParseError::Parse)?;
+
+        let multiplier = match unit {
+            "ns" => 1,
+            "us" | "µs" => 1_000,
+            "ms" => 1_000_000,
+            "" | "s" => NANOS_PER_SEC,
+            "m" | "min" => 60 * NANOS_PER_SEC,
+            "h" => 3600 * NANOS_PER_SEC,
+            "d" => 86400 * NANOS_PER_SEC,
+            _ => {
+                return Err(Utf8OrParseError::Parse(InvalidQuantity::InvalidUnit(
+                    unit.into(),
+                )))
+            }
+        };
+
+        let nanos = number.scale(multiplier);
+        let secs = u64::try_from(nanos / NANOS_PER_SEC)
+            .map_err(|_| Utf8OrParseError::Parse(InvalidQuantity::Overflow))?;
+
+        Ok(Duration::new(secs, (nanos % NANOS_PER_SEC) as u32))
+    }
+}
+
+/// A number of bytes, like `4096`, `10M`, or `4KiB`.
+///
+/// The value is a decimal number, optionally followed by a unit. Units are
+/// case-insensitive, and follow the same rules as GNU coreutils:
+///
+/// * A single letter (`K`, `M`, `G`, `T`, `P`, `E`), or a letter followed by
+///   `iB` (`KiB`, `MiB`, …), is a power of 1024.
+/// * A letter followed by `B` (`KB`, `MB`, …) is a power of 1000.
+///
+/// Values without a unit, or with the `B` unit, are bytes. Fractions are
+/// rounded down to a whole number of bytes, so `1.5K` is `1536`.
+///
+/// # Example
+///
+/// ```
+/// # use bash_builtins::BuiltinOptions;
+/// use bash_builtins::convert::ByteSize;
+///
+/// #[derive(BuiltinOptions)]
+/// struct Opt {
+///     #[opt = 's']
+///     limit: Option<ByteSize>,
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ByteSize(u64);
+
+impl ByteSize {
+    /// Creates a new instance with the given number of bytes.
+    pub const fn new(bytes: u64) -> Self {
+        ByteSize(bytes)
+    }
+
+    /// Number of bytes.
+    pub const fn bytes(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<ByteSize> for u64 {
+    fn from(size: ByteSize) -> u64 {
+        size.0
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+impl<'a> FromWordPointer<'a> for ByteSize {
+    type Err = Utf8OrParseError<InvalidQuantity>;
+
+    fn from_cstr(s: &'a CStr) -> Result<Self, Self::Err> {
+        let s = s.to_str().map_err(Utf8OrParseError::Utf8)?;
+        let (number, unit) = split_unit(s).map_err(Utf8OrParseError::Parse)?;
+
+        let multiplier = byte_multiplier(unit)
+            .ok_or_else(|| Utf8OrParseError::Parse(InvalidQuantity::InvalidUnit(unit.into())))?;
+
+        number
+            .scale(multiplier)
+            .try_into()
+            .map(ByteSize)
+            .map_err(|_| Utf8OrParseError::Parse(InvalidQuantity::Overflow))
+    }
+}
+
+fn byte_multiplier(unit: &str) -> Option<u128> {
+    let unit = unit.to_ascii_uppercase();
+
+    if unit.is_empty() || unit == "B" {
+        return Some(1);
+    }
+
+    let mut chars = unit.chars();
+    let exponent = match chars.next()? {
+        'K' => 1,
+        'M' => 2,
+        'G' => 3,
+        'T' => 4,
+        'P' => 5,
+        'E' => 6,
+        _ => return None,
+    };
+
+    let base: u128 = match chars.as_str() {
+        "" | "IB" => 1024,
+        "B" => 1000,
+        _ => return None,
+    };
+
+    Some(base.pow(exponent))
+}
+
+/// Decimal number parsed by `split_unit`.
+struct Decimal {
+    integer: u128,
+    fraction: u128,
+    fraction_digits: u32,
+}
+
+impl Decimal {
+    /// Multiply the number by `multiplier`, and round down the result.
+    ///
+    /// The result saturates to `u128::MAX`, so the caller will detect it as
+    /// an overflow.
+    fn scale(&self, multiplier: u128) -> u128 {
+        let fraction = self.fraction * multiplier / 10u128.pow(self.fraction_digits);
+        self.integer
+            .checked_mul(multiplier)
+            .and_then(|n| n.checked_add(fraction))
+            .unwrap_or(u128::MAX)
+    }
+}
+
+/// Split the leading number from the unit.
+fn split_unit(s: &str) -> Result<(Decimal, &str), InvalidQuantity> {
+    let unit_start = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+
+    let (number, unit) = s.split_at(unit_start);
+    let (integer, fraction) = match number.split_once('.') {
+        Some((i, f)) => (i, f),
+        None => (number, ""),
+    };
+
+    if (integer.is_empty() && fraction.is_empty()) || fraction.contains('.') {
+        return Err(InvalidQuantity::InvalidNumber);
+    }
+
+    let integer = if integer.is_empty() {
+        0
+    } else {
+        integer.parse().map_err(|_| InvalidQuantity::Overflow)?
+    };
+
+    let fraction = &fraction[..fraction.len().min(MAX_FRACTION_DIGITS as usize)];
+    let decimal = Decimal {
+        integer,
+        fraction: if fraction.is_empty() {
+            0
+        } else {
+            fraction.parse().unwrap_or(0)
+        },
+        fraction_digits: fraction.len() as u32,
+    };
+
+    Ok((decimal, unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    fn parse<T: for<'a> FromWordPointer<'a, Err = Utf8OrParseError<InvalidQuantity>>>(
+        s: &str,
+    ) -> Result<T, InvalidQuantity> {
+        let s = CString::new(s).unwrap();
+        T::from_cstr(&s).map_err(|e| match e {
+            Utf8OrParseError::Parse(e) => e,
+            Utf8OrParseError::Utf8(e) => panic!("{}", e),
+        })
+    }
+
+    #[test]
+    fn durations() {
+        let d = |s| parse::<Duration>(s);
+
+        assert_eq!(d("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(d("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(d("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(d("10us"), Ok(Duration::from_micros(10)));
+        assert_eq!(d("10µs"), Ok(Duration::from_micros(10)));
+        assert_eq!(d("7ns"), Ok(Duration::from_nanos(7)));
+        assert_eq!(d(".5m"), Ok(Duration::from_secs(30)));
+        assert_eq!(d("2min"), Ok(Duration::from_secs(120)));
+        assert_eq!(d("1h"), Ok(Duration::from_secs(3600)));
+        assert_eq!(d("1d"), Ok(Duration::from_secs(86400)));
+        assert_eq!(d("0.0000000019"), Ok(Duration::from_nanos(1)));
+
+        assert_eq!(d(""), Err(InvalidQuantity::InvalidNumber));
+        assert_eq!(d("s"), Err(InvalidQuantity::InvalidNumber));
+        assert_eq!(d("1.2.3s"), Err(InvalidQuantity::InvalidNumber));
+        assert_eq!(d("-1s"), Err(InvalidQuantity::InvalidNumber));
+        assert_eq!(d("1 s"), Err(InvalidQuantity::InvalidUnit(" s".into())));
+        assert_eq!(d("3w"), Err(InvalidQuantity::InvalidUnit("w".into())));
+        assert_eq!(d("99999999999999999999d"), Err(InvalidQuantity::Overflow));
+    }
+
+    #[test]
+    fn byte_sizes() {
+        let b = |s| parse::<ByteSize>(s).map(ByteSize::bytes);
+
+        assert_eq!(b("100"), Ok(100));
+        assert_eq!(b("100B"), Ok(100));
+        assert_eq!(b("4k"), Ok(4096));
+        assert_eq!(b("4KiB"), Ok(4096));
+        assert_eq!(b("4KB"), Ok(4000));
+        assert_eq!(b("10M"), Ok(10 << 20));
+        assert_eq!(b("1.5K"), Ok(1536));
+        assert_eq!(b("2gb"), Ok(2_000_000_000));
+        assert_eq!(b("1E"), Ok(1 << 60));
+
+        assert_eq!(b("16E"), Err(InvalidQuantity::Overflow));
+        assert_eq!(b("1Q"), Err(InvalidQuantity::InvalidUnit("Q".into())));
+        assert_eq!(b("1KiBB"), Err(InvalidQuantity::InvalidUnit("KiBB".into())));
+        assert_eq!(b("K"), Err(InvalidQuantity::InvalidNumber));
+    }
+}
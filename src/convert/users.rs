@@ -0,0 +1,119 @@
+//! Users and groups.
+
+use super::FromWordPointer;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::{c_char, c_int};
+use std::{mem, ptr};
+
+/// A user, from its name or its numeric id.
+///
+/// Names are resolved with `getpwnam_r(3)`. If there is no user with that
+/// name, and the value is a number, it is used as the user id, like `chown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct User(libc::uid_t);
+
+impl User {
+    /// User id.
+    pub fn uid(self) -> libc::uid_t {
+        self.0
+    }
+}
+
+/// A group, from its name or its numeric id.
+///
+/// Names are resolved with `getgrnam_r(3)`. Like [`User`], numbers are
+/// accepted when there is no group with that name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Group(libc::gid_t);
+
+impl Group {
+    /// Group id.
+    pub fn gid(self) -> libc::gid_t {
+        self.0
+    }
+}
+
+/// Error returned when a user or a group can't be found.
+#[derive(Debug)]
+pub enum InvalidId {
+    /// No user with the given name.
+    User,
+
+    /// No group with the given name.
+    Group,
+}
+
+impl fmt::Display for InvalidId {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidId::User => fmt.write_str("no such user"),
+            InvalidId::Group => fmt.write_str("no such group"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidId {}
+
+impl<'a> FromWordPointer<'a> for User {
+    type Err = InvalidId;
+
+    fn from_cstr(s: &'a CStr) -> Result<Self, Self::Err> {
+        let uid = lookup(s, |buf, len| unsafe {
+            let mut pwd: libc::passwd = mem::zeroed();
+            let mut result = ptr::null_mut();
+            let ret = libc::getpwnam_r(s.as_ptr(), &mut pwd, buf, len, &mut result);
+            (ret, (!result.is_null()).then_some(pwd.pw_uid))
+        });
+
+        uid.or_else(|| numeric_id(s))
+            .map(User)
+            .ok_or(InvalidId::User)
+    }
+}
+
+impl<'a> FromWordPointer<'a> for Group {
+    type Err = InvalidId;
+
+    fn from_cstr(s: &'a CStr) -> Result<Self, Self::Err> {
+        let gid = lookup(s, |buf, len| unsafe {
+            let mut grp: libc::group = mem::zeroed();
+            let mut result = ptr::null_mut();
+            let ret = libc::getgrnam_r(s.as_ptr(), &mut grp, buf, len, &mut result);
+            (ret, (!result.is_null()).then_some(grp.gr_gid))
+        });
+
+        gid.or_else(|| numeric_id(s))
+            .map(Group)
+            .ok_or(InvalidId::Group)
+    }
+}
+
+/// Call a `get*nam_r` function, with a buffer large enough for the entry.
+fn lookup<T>(
+    name: &CStr,
+    mut f: impl FnMut(*mut c_char, usize) -> (c_int, Option<T>),
+) -> Option<T> {
+    if name.to_bytes().is_empty() {
+        return None;
+    }
+
+    let mut buf = vec![0 as c_char; 1024];
+
+    loop {
+        match f(buf.as_mut_ptr(), buf.len()) {
+            (libc::ERANGE, _) if buf.len() < 1 << 20 => buf.resize(buf.len() * 2, 0),
+            (_, result) => return result,
+        }
+    }
+}
+
+fn numeric_id(s: &CStr) -> Option<u32> {
+    let s = s.to_str().ok()?;
+
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    s.parse().ok()
+}
@@ -0,0 +1,107 @@
+//! Types to create the builtin instances.
+
+use crate::{Builtin, SharedBuiltin};
+use std::fmt;
+
+/// A builtin instance with a warning to show when the builtin is loaded.
+///
+/// The function given in the `create` or `try_create` arguments of
+/// [`builtin_metadata!()`] can return this type to report a problem that does
+/// not prevent the builtin from being loaded, like a missing configuration
+/// file.
+///
+/// The warning is printed with [`log::warning`](crate::log::warning).
+///
+/// # Example
+///
+/// ```
+/// use bash_builtins::{builtin_metadata, Args, Builtin, Result, WithWarning};
+/// use std::fs;
+///
+/// builtin_metadata!(
+/// #   name = "x",
+///     // …
+///     create = Foo::new,
+/// );
+///
+/// struct Foo {
+///     config: Option<String>,
+/// }
+///
+/// impl Foo {
+///     fn new() -> WithWarning<Foo> {
+///         match fs::read_to_string("/some/config/file") {
+///             Ok(config) => WithWarning::none(Foo { config: Some(config) }),
+///             Err(e) => WithWarning::new(Foo { config: None }, e),
+///         }
+///     }
+/// }
+///
+/// impl Builtin for Foo {
+///     fn call(&mut self, args: &mut Args) -> Result<()> {
+/// #       let _ = args;
+///         // …
+///         Ok(())
+///     }
+/// }
+/// ```
+///
+/// [`builtin_metadata!()`]: crate::builtin_metadata
+pub struct WithWarning<T> {
+    instance: T,
+    warning: Option<String>,
+}
+
+impl<T> WithWarning<T> {
+    /// Returns the `instance`, with a `warning` to show when the builtin is
+    /// loaded.
+    pub fn new(instance: T, warning: impl fmt::Display) -> Self {
+        WithWarning {
+            instance,
+            warning: Some(warning.to_string()),
+        }
+    }
+
+    /// Returns the `instance`, without any warning.
+    pub fn none(instance: T) -> Self {
+        WithWarning {
+            instance,
+            warning: None,
+        }
+    }
+}
+
+/// Conversion from the value returned by `create` and `try_create` to a
+/// builtin instance.
+///
+/// `B` is `dyn SharedBuiltin` if the builtin is reentrant, so the instance
+/// must implement that trait.
+#[doc(hidden)]
+pub trait IntoBuiltin<B: ?Sized = dyn Builtin> {
+    /// Returns the instance, and the warning to print, if any.
+    fn into_builtin(self) -> (Box<B>, Option<String>);
+}
+
+impl<T: Builtin + 'static> IntoBuiltin for T {
+    fn into_builtin(self) -> (Box<dyn Builtin>, Option<String>) {
+        (Box::new(self), None)
+    }
+}
+
+impl<T: Builtin + 'static> IntoBuiltin for WithWarning<T> {
+    fn into_builtin(self) -> (Box<dyn Builtin>, Option<String>) {
+        (Box::new(self.instance), self.warning)
+    }
+}
+
+impl<T: SharedBuiltin + 'static> IntoBuiltin<dyn SharedBuiltin> for T {
+    fn into_builtin(self) -> (Box<dyn SharedBuiltin>, Option<String>) {
+        (Box::new(self), None)
+    }
+}
+
+impl<T: SharedBuiltin + 'static> IntoBuiltin<dyn SharedBuiltin> for WithWarning<T> {
+    fn into_builtin(self) -> (Box<dyn SharedBuiltin>, Option<String>) {
+        (Box::new(self.instance), self.warning)
+    }
+}
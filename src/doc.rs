@@ -0,0 +1,504 @@
+//! Support for documentation generated from the options of a builtin.
+//!
+//! The [`BuiltinOptions`] and [`BuiltinCommands`] derive macros implement
+//! [`OptionsDoc`], and the [`builtin_metadata!()`] macro uses it when the
+//! `options` key is present.
+//!
+//! [`BuiltinCommands`]: bash_builtins_macro::BuiltinCommands
+//! [`BuiltinOptions`]: bash_builtins_macro::BuiltinOptions
+//! [`builtin_metadata!()`]: bash_builtins_macro::builtin_metadata
+
+use crate::ffi::{self, BashBuiltin};
+use std::cell::Cell;
+use std::ffi::{CStr, CString};
+use std::io::{self, Write};
+use std::os::raw::c_char;
+use std::ptr;
+
+/// Trait implemented by the `BuiltinOptions` and `BuiltinCommands` derive
+/// macros.
+pub trait OptionsDoc {
+    /// Options accepted by the parser.
+    const OPTIONS: &'static [OptionDoc];
+
+    /// Usage of the positional arguments, like `path [count ...]`.
+    const ARGUMENTS: &'static str = "";
+
+    /// Subcommands accepted by the parser.
+    const COMMANDS: &'static [CommandDoc] = &[];
+}
+
+/// Documentation for a single option.
+pub struct OptionDoc {
+    /// Character for the short option (`-x`).
+    pub short: Option<char>,
+
+    /// `true` if the short option is used as `+x`.
+    pub plus: bool,
+
+    /// Name for the long option (`--name`).
+    pub long: Option<&'static str>,
+
+    /// Name to show for the argument of the option.
+    pub value_name: &'static str,
+
+    /// `0` if the option has no argument. Otherwise, the same character used
+    /// in the string for `internal_getopt`.
+    pub argument: u8,
+
+    /// `true` if the option must be present.
+    pub required: bool,
+
+    /// Valid values for the argument, if they are a fixed list.
+    pub choices: &'static [&'static str],
+
+    /// Text from the doc comments. Empty if the option is not documented.
+    pub doc: &'static str,
+}
+
+impl OptionDoc {
+    /// Short name, if present, or the long name, as typed in the command-line.
+    fn name(&self) -> String {
+        match (self.short, self.long) {
+            (Some(short), _) => format!("{}{}", self.prefix(), short),
+            (None, Some(long)) => format!("--{}", long),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// Prefix of the short option.
+    const fn prefix(&self) -> char {
+        if self.plus {
+            '+'
+        } else {
+            '-'
+        }
+    }
+
+    /// Usage of the option, like `[-a value]`.
+    fn usage(&self) -> String {
+        let name = self.name();
+
+        let usage = match (self.argument, self.short) {
+            (0, _) => name,
+            (b';', Some(_)) => format!("{} [{}]", name, self.value_name),
+            (b';', None) => format!("{}[={}]", name, self.value_name),
+            _ => format!("{} {}", name, self.value_name),
+        };
+
+        if self.required {
+            usage
+        } else {
+            format!("[{}]", usage)
+        }
+    }
+
+    /// Line for the `Options:` section of the help text.
+    fn help_line(&self) -> String {
+        let names = match (self.short, self.long) {
+            (Some(short), Some(long)) => format!("-{}, --{}", short, long),
+            (Some(short), None) => format!("{}{}", self.prefix(), short),
+            (None, Some(long)) => format!("    --{}", long),
+            (None, None) => String::new(),
+        };
+
+        if self.choices.is_empty() {
+            format!("  {}\t{}", names, self.doc)
+        } else {
+            let choices = format!("[values: {}]", self.choices.join(", "));
+            format!("  {}\t{}", names, join_words(&[self.doc, &choices]))
+        }
+    }
+}
+
+/// Returns a line with the usage of all options, like `[-a] [-b value]`.
+pub fn usage(options: &[OptionDoc]) -> String {
+    let items: Vec<_> = options.iter().map(OptionDoc::usage).collect();
+    items.join(" ")
+}
+
+/// Returns the lines of the `Options:` section of the help text.
+///
+/// If no option has a doc comment or a list of choices, the returned vector
+/// is empty.
+pub fn options_help(options: &[OptionDoc]) -> Vec<String> {
+    if options
+        .iter()
+        .all(|o| o.doc.is_empty() && o.choices.is_empty())
+    {
+        return Vec::new();
+    }
+
+    let mut lines = vec!["Options:".to_string()];
+    lines.extend(options.iter().map(OptionDoc::help_line));
+    lines
+}
+
+/// Documentation for a subcommand.
+pub struct CommandDoc {
+    /// Name typed in the command-line.
+    pub name: &'static str,
+
+    /// Options accepted by the parser of the subcommand.
+    pub options: &'static [OptionDoc],
+
+    /// Usage of the positional arguments of the subcommand.
+    pub arguments: &'static str,
+
+    /// Text from the doc comments. Empty if the subcommand is not documented.
+    pub doc: &'static str,
+}
+
+impl CommandDoc {
+    /// Usage of the subcommand, like `get [-d value] key`.
+    fn usage(&self) -> String {
+        join_words(&[self.name, &usage(self.options), self.arguments])
+    }
+
+    /// Print the usage of the subcommand, like `builtin_usage()`.
+    fn show_usage(&self) {
+        let name = command_name();
+        let _ = writeln!(io::stderr(), "{}: usage: {} {}", name, name, self.usage());
+    }
+
+    /// Print the help of the subcommand, like `builtin_help()`.
+    fn show_help(&self) {
+        let name = command_name();
+
+        let mut lines = Vec::new();
+        if !self.doc.is_empty() {
+            lines.push(self.doc.to_owned());
+        }
+
+        append_section(&mut lines, options_help(self.options));
+
+        let stdout = io::stdout();
+        let mut output = stdout.lock();
+        let _ = writeln!(output, "{}: {} {}", name, name, self.usage());
+        for line in lines {
+            let _ = writeln!(output, "    {}", line);
+        }
+    }
+}
+
+/// Returns the lines of the `Commands:` section of the help text.
+pub fn commands_help(commands: &[CommandDoc]) -> Vec<String> {
+    if commands.is_empty() {
+        return Vec::new();
+    }
+
+    let mut lines = vec!["Commands:".to_string()];
+    lines.extend(
+        commands
+            .iter()
+            .map(|command| format!("  {}\t{}", command.usage(), command.doc)),
+    );
+    lines
+}
+
+thread_local! {
+    /// Subcommand selected by `Args::subcommand`.
+    static CURRENT_COMMAND: Cell<Option<&'static CommandDoc>> = const { Cell::new(None) };
+}
+
+/// Update the subcommand used by `show_usage` and `show_help`.
+///
+/// Returns the previous value.
+pub(crate) fn set_current_command(
+    command: Option<&'static CommandDoc>,
+) -> Option<&'static CommandDoc> {
+    CURRENT_COMMAND.with(|current| current.replace(command))
+}
+
+/// If a subcommand is selected, print its usage and return `true`.
+pub(crate) fn show_command_usage() -> bool {
+    match CURRENT_COMMAND.with(Cell::get) {
+        Some(command) => {
+            command.show_usage();
+            true
+        }
+
+        None => false,
+    }
+}
+
+/// If a subcommand is selected, print its help and return `true`.
+pub(crate) fn show_command_help() -> bool {
+    match CURRENT_COMMAND.with(Cell::get) {
+        Some(command) => {
+            command.show_help();
+            true
+        }
+
+        None => false,
+    }
+}
+
+/// Name used to invoke the builtin.
+fn command_name() -> String {
+    unsafe {
+        if ffi::this_command_name.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(ffi::this_command_name)
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+}
+
+/// Join the non-empty items with a space.
+fn join_words(items: &[&str]) -> String {
+    let items: Vec<_> = items.iter().filter(|s| !s.is_empty()).copied().collect();
+    items.join(" ")
+}
+
+/// Add the lines of a section, with an empty line before it.
+fn append_section(lines: &mut Vec<String>, section: Vec<String>) {
+    if !lines.is_empty() && !section.is_empty() {
+        lines.push(String::new());
+    }
+
+    lines.extend(section);
+}
+
+/// Documentation for a builtin, built when the builtin is loaded.
+///
+/// The fields of the `BashBuiltin` struct point to the data owned by this
+/// value, so it has to live until the builtin is removed.
+pub struct BuiltinDoc {
+    short_doc: CString,
+
+    // Only used to keep the strings referenced by `long_doc_ptrs`.
+    #[allow(dead_code)]
+    long_doc: Vec<CString>,
+
+    long_doc_ptrs: Vec<*const c_char>,
+}
+
+// The pointers in `long_doc_ptrs` reference the strings in `long_doc`.
+unsafe impl Send for BuiltinDoc {}
+
+impl BuiltinDoc {
+    /// Build the documentation for the builtin `name`.
+    ///
+    /// If `short_doc` is `None`, it is generated from the options. The help
+    /// text for the options and the subcommands is added after `long_doc`.
+    pub fn new<T: OptionsDoc>(name: &str, short_doc: Option<&str>, long_doc: Option<&str>) -> Self {
+        let short_doc = match short_doc {
+            Some(s) => s.to_owned(),
+
+            None if !T::COMMANDS.is_empty() => {
+                let names: Vec<_> = T::COMMANDS.iter().map(|c| c.name).collect();
+                format!("{} {} [args]", name, names.join("|"))
+            }
+
+            None => join_words(&[name, &usage(T::OPTIONS), T::ARGUMENTS]),
+        };
+
+        let mut lines = Vec::new();
+        lines.extend(long_doc.map(str::to_owned));
+
+        append_section(&mut lines, options_help(T::OPTIONS));
+        append_section(&mut lines, commands_help(T::COMMANDS));
+
+        let long_doc: Vec<_> = lines.into_iter().map(cstring).collect();
+
+        let long_doc_ptrs = long_doc
+            .iter()
+            .map(|s| s.as_ptr())
+            .chain(Some(ptr::null()))
+            .collect();
+
+        BuiltinDoc {
+            short_doc: cstring(short_doc),
+            long_doc,
+            long_doc_ptrs,
+        }
+    }
+
+    /// Update the documentation fields of `builtin`.
+    ///
+    /// # Safety
+    ///
+    /// This value has to live until the builtin is removed.
+    pub unsafe fn apply(&self, builtin: *mut BashBuiltin) {
+        (*builtin).short_doc = self.short_doc.as_ptr();
+        (*builtin).long_doc = self.long_doc_ptrs.as_ptr();
+    }
+}
+
+fn cstring(s: String) -> CString {
+    CString::new(s.replace('\0', "")).unwrap_or_default()
+}
+
+/// Returns `true` if `name` (like `-a`, `+a`, or `--name`) is one of the
+/// `options`.
+///
+/// This function is used to emit warnings in the code generated by the
+/// `builtin_metadata!()` macro.
+pub const fn has_option(options: &[OptionDoc], name: &str) -> bool {
+    let mut i = 0;
+    while i < options.len() {
+        if is_name(&options[i], name) {
+            return true;
+        }
+
+        i += 1;
+    }
+
+    false
+}
+
+/// Returns `true` if every option is either in the `names` list, or documented
+/// in its doc comments.
+pub const fn all_documented(options: &[OptionDoc], names: &[&str]) -> bool {
+    let mut i = 0;
+    'options: while i < options.len() {
+        let option = &options[i];
+        i += 1;
+
+        if !option.doc.is_empty() {
+            continue;
+        }
+
+        let mut j = 0;
+        while j < names.len() {
+            if is_name(option, names[j]) {
+                continue 'options;
+            }
+
+            j += 1;
+        }
+
+        return false;
+    }
+
+    true
+}
+
+/// Returns `true` if `name` is the short or long name of `option`.
+const fn is_name(option: &OptionDoc, name: &str) -> bool {
+    let name = name.as_bytes();
+
+    if name.len() == 2 && name[0] == option.prefix() as u8 {
+        if let Some(short) = option.short {
+            return short as u32 == name[1] as u32;
+        }
+    }
+
+    if name.len() > 2 && name[0] == b'-' && name[1] == b'-' {
+        if let Some(long) = option.long {
+            let long = long.as_bytes();
+            if long.len() + 2 != name.len() {
+                return false;
+            }
+
+            let mut i = 0;
+            while i < long.len() {
+                if long[i] != name[i + 2] {
+                    return false;
+                }
+
+                i += 1;
+            }
+
+            return true;
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPTIONS: &[OptionDoc] = &[
+        OptionDoc {
+            short: Some('v'),
+            plus: false,
+            long: Some("verbose"),
+            value_name: "value",
+            argument: 0,
+            required: false,
+            choices: &[],
+            doc: "Show more details.",
+        },
+        OptionDoc {
+            short: Some('l'),
+            plus: false,
+            long: None,
+            value_name: "limit",
+            argument: b':',
+            required: true,
+            choices: &[],
+            doc: "",
+        },
+        OptionDoc {
+            short: None,
+            plus: false,
+            long: Some("color"),
+            value_name: "when",
+            argument: b';',
+            required: false,
+            choices: &["auto", "always", "never"],
+            doc: "",
+        },
+        OptionDoc {
+            short: Some('v'),
+            plus: true,
+            long: None,
+            value_name: "value",
+            argument: 0,
+            required: false,
+            choices: &[],
+            doc: "Show less details.",
+        },
+    ];
+
+    #[test]
+    fn usage_line() {
+        assert_eq!(usage(OPTIONS), "[-v] -l limit [--color[=when]] [+v]");
+    }
+
+    #[test]
+    fn help_lines() {
+        assert_eq!(
+            options_help(OPTIONS),
+            [
+                "Options:",
+                "  -v, --verbose\tShow more details.",
+                "  -l\t",
+                "      --color\t[values: auto, always, never]",
+                "  +v\tShow less details.",
+            ]
+        );
+
+        assert!(options_help(&OPTIONS[1..2]).is_empty());
+
+        assert_eq!(
+            options_help(&OPTIONS[1..3]),
+            [
+                "Options:",
+                "  -l\t",
+                "      --color\t[values: auto, always, never]",
+            ]
+        );
+    }
+
+    #[test]
+    fn find_options() {
+        assert!(has_option(OPTIONS, "-v"));
+        assert!(has_option(OPTIONS, "--verbose"));
+        assert!(has_option(OPTIONS, "--color"));
+        assert!(has_option(OPTIONS, "+v"));
+        assert!(!has_option(OPTIONS, "+l"));
+        assert!(!has_option(OPTIONS, "-x"));
+        assert!(!has_option(OPTIONS, "--colors"));
+        assert!(!has_option(OPTIONS, "--"));
+
+        assert!(all_documented(OPTIONS, &["-l", "--color"]));
+        assert!(!all_documented(OPTIONS, &["-l"]));
+    }
+}
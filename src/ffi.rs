@@ -1,4 +1,4 @@
-use std::os::raw::{c_char, c_int};
+use std::os::raw::{c_char, c_int, c_void};
 
 #[repr(C)]
 #[doc(hidden)]
@@ -41,16 +41,24 @@ pub struct BashBuiltin {
 pub(crate) const GETOPT_EOF: c_int = -1;
 pub(crate) const GETOPT_HELP: c_int = -99;
 
+/// Value returned by `internal_getopt` for invalid options or missing
+/// arguments, after printing the error.
+pub(crate) const GETOPT_INVALID: c_int = b'?' as c_int;
+
 extern "C" {
     pub(crate) static mut list_optarg: *const c_char;
     pub(crate) static mut list_opttype: c_int;
     pub(crate) static mut list_optopt: c_int;
     pub(crate) static mut loptend: *const WordList;
+    pub(crate) static mut lcurrent: *const WordList;
+
+    pub(crate) static this_command_name: *const c_char;
 
     pub(crate) fn internal_getopt(_: *const WordList, _: *const c_char) -> c_int;
     pub(crate) fn reset_internal_getopt();
 
     pub(crate) fn sh_needarg(_: *const c_char);
+    pub(crate) fn sh_invalidopt(_: *const c_char);
     pub(crate) fn no_options(_: *const WordList) -> c_int;
 
     pub(crate) fn builtin_error(_: *const c_char, ...);
@@ -59,6 +67,16 @@ extern "C" {
     pub(crate) fn builtin_help();
 
     pub(crate) fn internal_error(_: *const c_char, ...);
+
+    pub(crate) fn decode_signal(_: *const c_char, _: c_int) -> c_int;
+    pub(crate) fn signal_name(_: c_int) -> *const c_char;
+
+    pub(crate) fn evalexp(_: *const c_char, _: c_int, _: *mut c_int) -> libc::intmax_t;
+    pub(crate) static mut unbound_vars_is_error: c_int;
+
+    fn begin_unwind_frame(_: *const c_char);
+    fn discard_unwind_frame(_: *const c_char);
+    fn add_unwind_protect(_: extern "C" fn(*mut c_void), _: *mut c_void);
 }
 
 pub(crate) mod variables {
@@ -66,8 +84,15 @@ pub(crate) mod variables {
     use std::os::raw::{c_char, c_int, c_uint, c_void};
 
     // Flags for the `attributes` field.
+    pub const ATT_READONLY: c_int = 0x0000002;
     pub const ATT_ARRAY: c_int = 0x0000004;
     pub const ATT_ASSOC: c_int = 0x0000040;
+    pub const ATT_INVISIBLE: c_int = 0x0001000;
+    pub const ATT_NOASSIGN: c_int = 0x0004000;
+
+    // Flags for `find_or_make_array_variable`.
+    pub const FIND_ARRAY_ASSIGN: c_int = 0x01;
+    pub const FIND_ARRAY_ASSOC: c_int = 0x02;
 
     type VarValueFn = unsafe extern "C" fn(*mut ShellVar) -> *const ShellVar;
 
@@ -112,19 +137,24 @@ pub(crate) mod variables {
         pub times_found: c_int,
     }
 
+    #[repr(C)]
     pub struct HashTable {
         pub bucket_array: *const *const BucketContents,
         pub nbuckets: c_int,
+        #[allow(dead_code)]
         pub nentries: c_int,
     }
 
     extern "C" {
         pub fn find_variable(_: *const c_char) -> *mut ShellVar;
+        pub fn find_variable_noref(_: *const c_char) -> *mut ShellVar;
+        pub fn valid_nameref_value(_: *const c_char, _: c_int) -> c_int;
         pub fn legal_identifier(_: *const c_char) -> c_int;
 
         pub fn array_walk(_: ArrayPtr, _: ArrayElementMapFn, _: *const c_void);
 
         pub fn bind_variable(_: *const c_char, _: *const c_char, _: c_int) -> *mut ShellVar;
+        pub fn bind_variable_value(_: *mut ShellVar, _: *const c_char, _: c_int) -> *mut ShellVar;
         pub fn unbind_variable(_: *const c_char) -> c_int;
 
         pub fn bind_array_variable(
@@ -144,7 +174,56 @@ pub(crate) mod variables {
 
         pub fn make_new_assoc_variable(_: *const c_char) -> *mut ShellVar;
 
+        pub fn find_or_make_array_variable(_: *const c_char, _: c_int) -> *mut ShellVar;
+
+        pub fn array_create() -> ArrayPtr;
+        pub fn array_insert(_: ArrayPtr, _: libc::intmax_t, _: *const c_char) -> c_int;
+        pub fn array_dispose(_: ArrayPtr);
+
+        pub fn hash_create(_: c_int) -> *mut HashTable;
+        pub fn assoc_insert(_: *mut HashTable, _: *const c_char, _: *const c_char) -> c_int;
+        pub fn assoc_dispose(_: *mut HashTable);
+
         pub fn get_exitstat(_: *const WordList) -> c_int;
+
+        /// Set to non-zero when the environment for child processes has to
+        /// be rebuilt, like after exporting a variable.
+        pub static mut array_needs_making: c_int;
+    }
+}
+
+/// Register a function to be called when the process exits.
+///
+/// The function is registered with `atexit`, so the C library also calls it
+/// if the shared object is removed with `dlclose`.
+pub fn at_exit(func: extern "C" fn()) {
+    unsafe {
+        libc::atexit(func);
+    }
+}
+
+/// Tag for the unwind frames created by [`unwind_protect`].
+const UNWIND_FRAME_TAG: &[u8] = b"bash_builtins_call\0";
+
+/// Register `cleanup` to be called if bash jumps to the top level before the
+/// current call is finished.
+///
+/// The function is added to a new unwind frame, which has to be removed with
+/// [`discard_unwind_protect`] when the call returns.
+#[doc(hidden)]
+pub fn unwind_protect(cleanup: extern "C" fn(*mut c_void)) {
+    unsafe {
+        begin_unwind_frame(UNWIND_FRAME_TAG.as_ptr().cast());
+        add_unwind_protect(cleanup, std::ptr::null_mut());
+    }
+}
+
+/// Remove the last frame created by [`unwind_protect`], without calling its
+/// function.
+#[doc(hidden)]
+pub fn discard_unwind_protect() {
+    unsafe {
+        discard_unwind_frame(UNWIND_FRAME_TAG.as_ptr().cast());
     }
 }
 
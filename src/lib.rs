@@ -58,12 +58,17 @@
 //!
 //! * The [`BuiltinOptions`] derive macro, to generate an option parser.
 //!
+//! * The [`BuiltinCommands`] derive macro, for builtins with subcommands.
+//!
+//! * The [`builtin`] attribute macro, to define a builtin from a function.
+//!
 //! * The [`Builtin`] trait, to provide the builtin functionality.
 //!
 //! * The [`Args`] type, to access to the command-line arguments.
 //!
 //! A single crate can contain multiple builtins. Each builtin requires its own
-//! call to [`builtin_metadata!()`].
+//! call to [`builtin_metadata!()`], unless they share the same instance, with
+//! the `names` argument of the macro.
 //!
 //! ## Basic Structure
 //!
@@ -160,7 +165,8 @@
 //! [`try_create`] is executed. This function will create a new instance of a
 //! type that implements the [`Builtin`] trait.
 //!
-//! [`try_create`] must be used if the initialization can fail.
+//! [`try_create`] must be used if the initialization can fail. If it returns
+//! an error, the error is printed and the builtin is not loaded.
 //!
 //! ## Example of a Fallible Initialization
 //!
@@ -194,6 +200,10 @@
 //! }
 //! ```
 //!
+//! If the builtin can be loaded, but the user should be notified about some
+//! problem, like a missing configuration file, the function can return a
+//! [`WithWarning`] value.
+//!
 //! [`create`]: bash_builtins_macro::builtin_metadata!()#create
 //! [`try_create`]: bash_builtins_macro::builtin_metadata!()#try_create
 //!
@@ -205,7 +215,23 @@
 //! must implement [`Drop`](std::ops::Drop). The value is dropped just before
 //! the builtin is deleted.
 //!
-//! The destructor is not executed when the Bash process exits.
+//! The destructor is not executed when the Bash process exits. Use
+//! [`Builtin::on_shell_exit`] for cleanup tasks that must be done before the
+//! shell exits.
+//!
+//! ## Lifecycle Hooks
+//!
+//! Besides [`Builtin::call`], the [`Builtin`] trait has methods that are
+//! invoked in other stages of the builtin lifecycle:
+//!
+//! * [`on_load`](Builtin::on_load), after `enable -f`.
+//! * [`on_unload`](Builtin::on_unload), before dropping the instance in
+//!   `enable -d`. It can return an error to print a warning.
+//! * [`on_shell_exit`](Builtin::on_shell_exit), when the shell exits.
+//! * [`on_subshell`](Builtin::on_subshell), when the instance is used for the
+//!   first time in a subshell.
+//!
+//! All of them have an empty default implementation.
 //!
 //! # Parsing Command Line Options
 //!
@@ -248,11 +274,7 @@
 //! const LIMIT_VAR_NAME: &str = "SOMENAME_LIMIT";
 //!
 //! fn get_limit() -> usize {
-//!     variables::find_as_string(LIMIT_VAR_NAME)
-//!         .as_ref()
-//!         .and_then(|v| v.to_str().ok())
-//!         .and_then(|v| v.parse().ok())
-//!         .unwrap_or(DEFAULT_LIMIT)
+//!     variables::get(LIMIT_VAR_NAME).unwrap_or(DEFAULT_LIMIT)
 //! }
 //! ```
 //!
@@ -267,15 +289,30 @@
 //! # Panic Handling
 //!
 //! Panics are captured with [`panic::catch_unwind`], so they should not reach
-//! the bash process.
+//! the bash process. The message and the location of the panic are printed
+//! with [`log::error`], and the exit code of the builtin is `101`.
 //!
-//! After a panic the builtin is [“poisoned”], and any attempt to use it will
-//! print the error `invalid internal state` on the terminal. Users will have
-//! to remove it (`enable -d`) and enable it again. Also, when a poisoned
-//! builtin is removed, its destructors (if any) are not executed.
+//! To print these messages, a [panic hook] is installed when the builtin is
+//! loaded, and the previous hook is restored when it is removed. If the shell
+//! variable `BASH_BUILTINS_BACKTRACE` is set, the message is followed by a
+//! backtrace:
 //!
-//! If you want to avoid this behaviour you have to use [`panic::catch_unwind`]
-//! in your own code.
+//! ```notrust
+//! $ BASH_BUILTINS_BACKTRACE=1 mybuiltin
+//! bash: mybuiltin: panicked at src/lib.rs:10:9: something failed
+//! stack backtrace:
+//!    0: …
+//! ```
+//!
+//! By default, after a panic the builtin is [“poisoned”], and any attempt to
+//! use it will print the error `invalid internal state` on the terminal. Users
+//! will have to remove it (`enable -d`) and enable it again. Also, when a
+//! poisoned builtin is removed, its destructors (if any) are not executed.
+//!
+//! This behaviour can be changed with the [`on_panic`] argument of
+//! [`builtin_metadata!()`], to create a new instance, or to keep using the
+//! same one. Another option is to use [`panic::catch_unwind`] in your own
+//! code.
 //!
 //! [“poisoned”]: https://doc.rust-lang.org/stable/std/sync/struct.Mutex.html#poisoning
 //!
@@ -284,27 +321,50 @@
 //! process.
 //!
 //! [`panic::catch_unwind`]: std::panic::catch_unwind
+//! [`on_panic`]: bash_builtins_macro::builtin_metadata!()#on_panic-optional
+//! [panic hook]: std::panic::set_hook
 //! [`panic` setting]: https://doc.rust-lang.org/cargo/reference/profiles.html#panic
+//! [`builtin`]: bash_builtins_macro::builtin
+//! [`BuiltinCommands`]: bash_builtins_macro::BuiltinCommands
 //! [`BuiltinOptions`]: bash_builtins_macro::BuiltinOptions
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 mod args;
+mod assignments;
+mod bit_flags;
+mod create;
 mod errors;
+mod words;
 
+pub mod arith;
 pub mod convert;
 pub mod log;
 pub mod variables;
 
+#[doc(hidden)]
+pub mod doc;
+
 #[doc(hidden)]
 pub mod ffi;
 
+#[doc(hidden)]
+pub mod panics;
+
 // Re-export macros.
-pub use bash_builtins_macro::{builtin_metadata, BuiltinOptions};
+pub use bash_builtins_macro::{
+    builtin, builtin_metadata, BuiltinCommands, BuiltinOptions, FromWordPointer,
+};
+
+#[doc(hidden)]
+pub use create::IntoBuiltin;
 
 // Re-export public items.
-pub use args::{Args, BuiltinOptions};
+pub use args::{Args, BuiltinOptions, FromArgs, LongOption, RawArguments, RawOptions};
+pub use assignments::{Assignment, InvalidAssignment};
+pub use create::WithWarning;
 pub use errors::{Error, Result};
+pub use words::{Word, WordFlags};
 
 /// The `Builtin` trait contains the implementation for a bash builtin.
 pub trait Builtin {
@@ -319,4 +379,59 @@ pub trait Builtin {
     ///
     /// Command-line arguments are read from the [`Args`] instance.
     fn call(&mut self, args: &mut Args) -> Result<()>;
+
+    /// Method invoked when the builtin is loaded with `enable -f`.
+    ///
+    /// `name` is the name of the loaded builtin. If the instance is shared by
+    /// multiple names (with the `names` argument of [`builtin_metadata!()`]),
+    /// this method is invoked for each one of them.
+    ///
+    /// If the instance is created in the first call (with `lazy = true`),
+    /// this method is invoked after creating it.
+    fn on_load(&mut self, name: &str) {
+        let _ = name;
+    }
+
+    /// Method invoked before the instance is dropped, when the builtin is
+    /// removed with `enable -d`.
+    ///
+    /// If it returns an error, the error is printed as a warning. Bash does
+    /// not provide any way to cancel the removal, so the instance is always
+    /// dropped after this method returns.
+    fn on_unload(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Method invoked when the shell exits.
+    ///
+    /// The instance is never dropped if the shell exits without removing the
+    /// builtin, so this method can be used to release resources that are not
+    /// released by the operating system, like temporary files.
+    ///
+    /// It is not invoked in subshells, if the shell is replaced with `exec`,
+    /// or if the shell exits while the builtin is running.
+    fn on_shell_exit(&mut self) {}
+
+    /// Method invoked the first time the instance is used in a subshell,
+    /// before [`call`](Builtin::call).
+    ///
+    /// The subshell is a copy of the main shell process, so the instance
+    /// shares resources like file descriptors or locks with the instance in
+    /// the main shell.
+    fn on_subshell(&mut self) {}
+}
+
+/// The `SharedBuiltin` trait is required by builtins that can be invoked
+/// while they are running, when the `reentrant` argument of
+/// [`builtin_metadata!()`] is `true`.
+pub trait SharedBuiltin: Builtin {
+    /// Method invoked when the builtin is typed in the prompt, instead of
+    /// [`Builtin::call`].
+    ///
+    /// It receives a shared reference to the instance, so the builtin can be
+    /// invoked again while this method is running, like when it executes a
+    /// shell function that calls the same builtin. Any mutable state has to
+    /// be kept in types like [`Cell`](std::cell::Cell) or
+    /// [`RefCell`](std::cell::RefCell).
+    fn call_shared(&self, args: &mut Args) -> Result<()>;
 }
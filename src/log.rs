@@ -6,18 +6,29 @@ use std::os::raw::{c_char, c_int};
 /// Shows the usage text for this builtin.
 ///
 /// This function should be used when an invalid option is found.
+///
+/// If a subcommand was selected by a [`BuiltinCommands`] parser, the usage of
+/// that subcommand is printed.
+///
+/// [`BuiltinCommands`]: bash_builtins_macro::BuiltinCommands
 #[inline]
 pub fn show_usage() {
-    unsafe {
-        ffi::builtin_usage();
+    if !crate::doc::show_command_usage() {
+        unsafe {
+            ffi::builtin_usage();
+        }
     }
 }
 
 /// Shows the help text for this builtin.
+///
+/// Like [`show_usage`], it uses the selected subcommand, if any.
 #[inline]
 pub fn show_help() {
-    unsafe {
-        ffi::builtin_help();
+    if !crate::doc::show_command_help() {
+        unsafe {
+            ffi::builtin_help();
+        }
     }
 }
 
@@ -31,6 +42,16 @@ pub fn missing_argument() {
     }
 }
 
+/// Display an error when an option is not valid.
+///
+/// It uses the `sh_invalidopt` function from Bash.
+pub fn invalid_option() {
+    unsafe {
+        let msg = [ffi::list_opttype as _, ffi::list_optopt as _, 0];
+        ffi::sh_invalidopt(msg.as_ptr());
+    }
+}
+
 macro_rules! log_fn {
     ($name:ident, $bash_fn:ident, $doc:literal) => {
         #[inline]
@@ -0,0 +1,166 @@
+//! Functions to catch panics in the builtins.
+//!
+//! A panic hook is installed when a builtin is loaded, and the previous hook
+//! is restored when it is removed. If the panic happens inside
+//! [`catch_unwind`], the hook saves its message and location, instead of
+//! printing them, so the code generated by `builtin_metadata!()` can show them
+//! with [`log::error`](crate::log::error).
+//!
+//! If the shell variable `BASH_BUILTINS_BACKTRACE` is set, a backtrace is
+//! printed after the message.
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe, Location};
+use std::sync::Mutex;
+
+/// Shell variable to enable backtraces.
+const BACKTRACE_VAR: &str = "BASH_BUILTINS_BACKTRACE";
+
+thread_local! {
+    /// `true` if the current thread is inside `catch_unwind`.
+    static CAPTURING: Cell<bool> = const { Cell::new(false) };
+
+    /// Panic captured by the hook.
+    static LAST_PANIC: RefCell<Option<PanicReport>> = const { RefCell::new(None) };
+}
+
+// `PanicInfo` is deprecated in newer versions of Rust, but `PanicHookInfo` is
+// not available in the minimum supported version.
+#[allow(deprecated)]
+type PanicHook = Box<dyn Fn(&panic::PanicInfo) + Sync + Send + 'static>;
+
+/// Number of builtins using the hook, and the hook to restore when all of
+/// them are removed.
+static HOOK: Mutex<(usize, Option<PanicHook>)> = Mutex::new((0, None));
+
+/// Message and location of a panic.
+#[derive(Debug)]
+pub struct PanicReport {
+    message: String,
+    location: Option<String>,
+    backtrace: Option<Backtrace>,
+}
+
+impl PanicReport {
+    fn new(payload: &(dyn Any + Send), location: Option<&Location>) -> Self {
+        let message = if let Some(s) = payload.downcast_ref::<&str>() {
+            (*s).to_owned()
+        } else if let Some(s) = payload.downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "Box<dyn Any>".to_owned()
+        };
+
+        PanicReport {
+            message,
+            location: location.map(|l| l.to_string()),
+            backtrace: None,
+        }
+    }
+
+    /// Message of the panic.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Location in the source code where the panic happened, if available.
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+
+    /// Backtrace of the panic, if `BASH_BUILTINS_BACKTRACE` was set.
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_ref()
+    }
+
+    /// Print the report with [`log::error`](crate::log::error), followed by
+    /// the backtrace, if any.
+    pub fn print(&self) {
+        crate::log::error(self.to_string());
+
+        if let Some(backtrace) = &self.backtrace {
+            let _ = writeln!(io::stderr(), "stack backtrace:\n{}", backtrace);
+        }
+    }
+}
+
+impl fmt::Display for PanicReport {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(fmt, "panicked at {}: {}", location, self.message),
+            None => write!(fmt, "panicked: {}", self.message),
+        }
+    }
+}
+
+/// Install the panic hook. It is invoked when a builtin is loaded.
+///
+/// Panics outside of [`catch_unwind`] are sent to the previous hook.
+pub fn install_hook() {
+    let mut hook = match HOOK.lock() {
+        Ok(hook) => hook,
+        Err(poison) => poison.into_inner(),
+    };
+
+    hook.0 += 1;
+    if hook.0 > 1 {
+        return;
+    }
+
+    let previous: PanicHook = panic::take_hook();
+    hook.1 = Some(previous);
+
+    panic::set_hook(Box::new(|info| {
+        if CAPTURING.with(Cell::get) {
+            let mut report = PanicReport::new(info.payload(), info.location());
+
+            if crate::variables::find_raw(BACKTRACE_VAR).is_some() {
+                report.backtrace = Some(Backtrace::force_capture());
+            }
+
+            LAST_PANIC.with(|last| *last.borrow_mut() = Some(report));
+        } else if let Ok(hook) = HOOK.lock() {
+            if let Some(previous) = &hook.1 {
+                previous(info);
+            }
+        }
+    }));
+}
+
+/// Restore the previous panic hook, if there are no more builtins using it.
+/// It is invoked when a builtin is removed.
+pub fn uninstall_hook() {
+    let mut hook = match HOOK.lock() {
+        Ok(hook) => hook,
+        Err(poison) => poison.into_inner(),
+    };
+
+    hook.0 = hook.0.saturating_sub(1);
+    if hook.0 > 0 {
+        return;
+    }
+
+    if let Some(previous) = hook.1.take() {
+        panic::set_hook(previous);
+    }
+}
+
+/// Invoke `f`, and return its result.
+///
+/// If `f` panics, returns a [`PanicReport`] with the message and the location
+/// of the panic.
+pub fn catch_unwind<F: FnOnce() -> R, R>(f: F) -> Result<R, PanicReport> {
+    let capturing = CAPTURING.with(|c| c.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CAPTURING.with(|c| c.set(capturing));
+
+    result.map_err(|payload| {
+        LAST_PANIC
+            .with(|last| last.borrow_mut().take())
+            .unwrap_or_else(|| PanicReport::new(&*payload, None))
+    })
+}
@@ -2,6 +2,7 @@
 
 use std::convert::TryFrom;
 use std::ffi::{c_int, c_void, CStr, CString};
+use std::ptr::NonNull;
 
 use super::VariableError;
 use crate::ffi::variables as ffi;
@@ -23,6 +24,7 @@ where
             return Err(VariableError::InvalidName);
         }
 
+        let value = super::assignment_value(ffi::find_variable(name.as_ptr()), value)?;
         ffi::bind_array_variable(name.as_ptr(), index as _, value.as_ptr(), 0)
     };
 
@@ -33,6 +35,100 @@ where
     }
 }
 
+/// Replace the contents of the shell variable referenced by `name` with an
+/// indexed array containing `values`.
+///
+/// The array is built before modifying the shell variable, so the previous
+/// contents are kept if any value is not valid. If the shell variable does
+/// not exist, it is created. If it contains a string, it is converted to an
+/// array, like `declare -a name=(…)`.
+///
+/// The values are not required to be valid UTF-8, but they can't contain any
+/// nul byte.
+///
+/// # Example
+///
+/// ```no_run
+/// use bash_builtins::variables;
+///
+/// # fn f() -> Result<(), variables::VariableError> {
+/// let lines = ["first", "second", "third"];
+/// variables::set_array("LINES", &lines)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn set_array<I>(name: &str, values: I) -> Result<(), VariableError>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let name = CString::new(name).map_err(|_| VariableError::InvalidName)?;
+
+    unsafe {
+        let current = ffi::find_variable(name.as_ptr());
+        let array = OwnedArray(ffi::array_create());
+
+        for (index, value) in values.into_iter().enumerate() {
+            let value = CString::new(value.as_ref()).map_err(|_| VariableError::InvalidValue)?;
+            let value = super::assignment_value(current, value)?;
+            ffi::array_insert(array.0, index as _, value.as_ptr());
+        }
+
+        let shell_var = find_or_make_array(&name, false)?;
+        let old_array = std::mem::replace(
+            &mut (*shell_var.as_ptr()).value,
+            array.into_raw().cast_mut(),
+        );
+
+        if !old_array.is_null() {
+            ffi::array_dispose(old_array.cast());
+        }
+    }
+
+    Ok(())
+}
+
+/// Append `values` to the indexed array contained in the shell variable
+/// referenced by `name`, like `name+=(…)`.
+///
+/// The new elements are added after the highest index in the array. If the
+/// shell variable does not exist, it is created. If it contains a string, it
+/// is converted to an array, and the string is its first element.
+///
+/// The values are not required to be valid UTF-8, but they can't contain any
+/// nul byte. They are checked before modifying the shell variable.
+pub fn append_array<I>(name: &str, values: I) -> Result<(), VariableError>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let name = CString::new(name).map_err(|_| VariableError::InvalidName)?;
+
+    unsafe {
+        let current = ffi::find_variable(name.as_ptr());
+
+        let values = values
+            .into_iter()
+            .map(|value| {
+                let value =
+                    CString::new(value.as_ref()).map_err(|_| VariableError::InvalidValue)?;
+                super::assignment_value(current, value)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let shell_var = find_or_make_array(&name, false)?;
+        let array: ffi::ArrayPtr = (*shell_var.as_ptr()).value.cast();
+
+        let first_index = array_max_index(array).map_or(0, |index| index + 1);
+
+        for (index, value) in (first_index..).zip(&values) {
+            ffi::array_insert(array, index as _, value.as_ptr());
+        }
+    }
+
+    Ok(())
+}
+
 /// Returns a copy of the value corresponding to an element in the array.
 pub fn array_get(name: &str, index: usize) -> Option<CString> {
     let var = super::find_raw(name)?;
@@ -77,6 +173,94 @@ pub fn array_get(name: &str, index: usize) -> Option<CString> {
     result
 }
 
+/// Find the shell variable referenced by `name`, or create a new one, to
+/// replace its contents with an array.
+///
+/// Strings are converted to arrays. It fails if the shell variable contains
+/// the other kind of array, or if it can't be modified.
+pub(super) unsafe fn find_or_make_array(
+    name: &CStr,
+    assoc: bool,
+) -> Result<NonNull<ffi::ShellVar>, VariableError> {
+    if ffi::legal_identifier(name.as_ptr()) == 0 {
+        return Err(VariableError::InvalidName);
+    }
+
+    // `find_or_make_array_variable` does not reject an associative array
+    // when an indexed array is requested, so both cases are checked here.
+    //
+    // Readonly variables are also checked here, like in `assignment_value`.
+    let current = ffi::find_variable(name.as_ptr());
+    if !current.is_null() {
+        let attributes = (*current).attributes;
+
+        if attributes & (ffi::ATT_READONLY | ffi::ATT_NOASSIGN) != 0 {
+            return Err(VariableError::Readonly);
+        }
+
+        if assoc && attributes & ffi::ATT_ARRAY != 0 {
+            return Err(VariableError::NotAssocArray);
+        }
+
+        if !assoc && attributes & ffi::ATT_ASSOC != 0 {
+            return Err(VariableError::NotIndexedArray);
+        }
+    }
+
+    let mut flags = ffi::FIND_ARRAY_ASSIGN;
+    if assoc {
+        flags |= ffi::FIND_ARRAY_ASSOC;
+    }
+
+    let shell_var = NonNull::new(ffi::find_or_make_array_variable(name.as_ptr(), flags))
+        .ok_or(VariableError::InvalidValue)?;
+
+    (*shell_var.as_ptr()).attributes &= !ffi::ATT_INVISIBLE;
+
+    Ok(shell_var)
+}
+
+/// An `ARRAY` created by `array_create`. It is released if it is not added
+/// to a shell variable.
+struct OwnedArray(ffi::ArrayPtr);
+
+impl OwnedArray {
+    fn into_raw(self) -> ffi::ArrayPtr {
+        let array = self.0;
+        std::mem::forget(self);
+        array
+    }
+}
+
+impl Drop for OwnedArray {
+    fn drop(&mut self) {
+        unsafe { ffi::array_dispose(self.0) }
+    }
+}
+
+/// Returns the highest index in the array, or `None` if it is empty.
+unsafe fn array_max_index(array: ffi::ArrayPtr) -> Option<i64> {
+    let mut max_index = None;
+
+    #[repr(C)]
+    struct Data(*mut Option<i64>);
+
+    unsafe extern "C" fn collect(elem: *mut ffi::ArrayElement, data: *mut c_void) -> c_int {
+        // Elements are visited in index order, so the last one is the
+        // highest.
+        (*data.cast::<Data>()).0.write(Some((*elem).ind));
+        1
+    }
+
+    ffi::array_walk(
+        array,
+        collect,
+        &Data(&mut max_index) as *const Data as *const c_void,
+    );
+
+    max_index
+}
+
 pub(crate) unsafe fn array_items(shell_var: *const ffi::ShellVar) -> Vec<(i64, CString)> {
     let array: ffi::ArrayPtr = unsafe { (*shell_var).value.cast() };
     let mut vec = Vec::new();
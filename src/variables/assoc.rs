@@ -31,8 +31,7 @@ where
     T1: AsRef<[u8]>,
 {
     let name = CString::new(name).map_err(|_| VariableError::InvalidName)?;
-    let key = cstrdup(key)?;
-    let value = cstrdup(value)?;
+    let value = CString::new(value.as_ref()).map_err(|_| VariableError::InvalidValue)?;
 
     let res = unsafe {
         if ffi::legal_identifier(name.as_ptr()) == 0 {
@@ -47,6 +46,10 @@ where
             return Err(VariableError::NotAssocArray);
         }
 
+        let value = super::assignment_value(shell_var, value)?;
+        let key = cstrdup(key)?;
+        let value = cstrdup(value.as_bytes())?;
+
         ffi::bind_assoc_variable(shell_var, name.as_ptr(), key, value, 0)
     };
 
@@ -57,6 +60,83 @@ where
     }
 }
 
+/// Replace the contents of the shell variable referenced by `name` with an
+/// associative array containing `items`.
+///
+/// The array is built before modifying the shell variable, so the previous
+/// contents are kept if any key or value is not valid. If the shell variable
+/// does not exist, it is created. If it contains a string, it is converted to
+/// an associative array, like `declare -A name=(…)`.
+///
+/// Keys and values are not required to be valid UTF-8, but they can't contain
+/// any nul byte.
+///
+/// # Example
+///
+/// ```no_run
+/// use bash_builtins::variables;
+/// use std::collections::HashMap;
+///
+/// # fn f() -> Result<(), variables::VariableError> {
+/// let mut sizes = HashMap::new();
+/// sizes.insert("small", "10");
+/// sizes.insert("large", "1000");
+///
+/// variables::set_assoc("SIZES", sizes)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn set_assoc<I, K, V>(name: &str, items: I) -> Result<(), VariableError>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    let name = CString::new(name).map_err(|_| VariableError::InvalidName)?;
+
+    unsafe {
+        let current = ffi::find_variable(name.as_ptr());
+        let table = OwnedTable(ffi::hash_create(0));
+
+        for (key, value) in items {
+            let value = CString::new(value.as_ref()).map_err(|_| VariableError::InvalidValue)?;
+            let value = super::assignment_value(current, value)?;
+
+            // The key is released by bash.
+            let key = cstrdup(key)?;
+            ffi::assoc_insert(table.0, key, value.as_ptr());
+        }
+
+        let shell_var = super::arrays::find_or_make_array(&name, true)?;
+        let old_table =
+            std::mem::replace(&mut (*shell_var.as_ptr()).value, table.into_raw().cast());
+
+        if !old_table.is_null() {
+            ffi::assoc_dispose(old_table.cast());
+        }
+    }
+
+    Ok(())
+}
+
+/// A `HASH_TABLE` created by `hash_create`. It is released if it is not added
+/// to a shell variable.
+struct OwnedTable(*mut ffi::HashTable);
+
+impl OwnedTable {
+    fn into_raw(self) -> *mut ffi::HashTable {
+        let table = self.0;
+        std::mem::forget(self);
+        table
+    }
+}
+
+impl Drop for OwnedTable {
+    fn drop(&mut self) {
+        unsafe { ffi::assoc_dispose(self.0) }
+    }
+}
+
 /// Returns a copy of the value corresponding to a key in an associative array.
 pub fn assoc_get<T: AsRef<[u8]>>(name: &str, key: T) -> Option<CString> {
     let key = key.as_ref();
@@ -84,7 +164,7 @@ pub(super) struct AssocItemsIterator<'a> {
 }
 
 impl AssocItemsIterator<'_> {
-    pub(super) unsafe fn new(table: &ffi::HashTable) -> AssocItemsIterator {
+    pub(super) unsafe fn new(table: &ffi::HashTable) -> AssocItemsIterator<'_> {
         AssocItemsIterator {
             table,
             num_bucket: 0,
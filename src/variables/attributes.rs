@@ -0,0 +1,118 @@
+//! Attributes of shell variables.
+
+use crate::bit_flags::bit_flags;
+use std::os::raw::c_int;
+
+/// Attributes of a shell variable, like the ones set by `declare`.
+///
+/// The values are the `att_*` constants defined in the `variables.h` file of
+/// bash.
+///
+/// Use [`find_attributes`](super::find_attributes) or
+/// [`RawVariable::attributes`](super::RawVariable::attributes) to get the
+/// attributes of a shell variable, and [`declare`](super::declare) to set
+/// them.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Attributes(c_int);
+
+bit_flags! {
+    Attributes {
+        /// `att_exported`: the variable is exported to child processes
+        /// (`declare -x`).
+        EXPORTED = 0x0000001;
+
+        /// `att_readonly`: the variable can't be modified (`declare -r`).
+        READONLY = 0x0000002;
+
+        /// `att_array`: the variable is an indexed array (`declare -a`).
+        ARRAY = 0x0000004;
+
+        /// `att_integer`: assignments are evaluated as arithmetic expressions
+        /// (`declare -i`).
+        INTEGER = 0x0000010;
+
+        /// `att_local`: the variable is local to a shell function.
+        LOCAL = 0x0000020;
+
+        /// `att_assoc`: the variable is an associative array (`declare -A`).
+        ASSOC = 0x0000040;
+
+        /// `att_trace`: the trace attribute (`declare -t`). It has no effect on
+        /// variables.
+        TRACE = 0x0000080;
+
+        /// `att_uppercase`: assigned values are converted to uppercase
+        /// (`declare -u`).
+        UPPERCASE = 0x0000100;
+
+        /// `att_lowercase`: assigned values are converted to lowercase
+        /// (`declare -l`).
+        LOWERCASE = 0x0000200;
+
+        /// `att_capcase`: the first character of assigned values is converted to
+        /// uppercase.
+        CAPCASE = 0x0000400;
+
+        /// `att_nameref`: the variable is a reference to another variable
+        /// (`declare -n`).
+        NAMEREF = 0x0000800;
+
+        /// `att_invisible`: the variable is declared, but it has no value.
+        INVISIBLE = 0x0001000;
+
+        /// `att_noassign`: assignments to the variable are ignored.
+        NOASSIGN = 0x0004000;
+
+        /// `att_imported`: the variable was imported from the environment.
+        IMPORTED = 0x0008000;
+
+        /// `att_special`: assignments to the variable have side effects.
+        SPECIAL = 0x0010000;
+    }
+}
+
+impl Attributes {
+    /// Creates an instance from the value of the `attributes` field in a
+    /// `SHELL_VAR` struct.
+    pub const fn from_bits(bits: c_int) -> Self {
+        Attributes(bits)
+    }
+
+    /// An instance without any attribute.
+    pub const fn empty() -> Self {
+        Attributes(0)
+    }
+
+    /// Raw value of the attributes.
+    pub const fn bits(self) -> c_int {
+        self.0
+    }
+
+    /// Returns `true` if no attribute is set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if all attributes in `other` are set.
+    pub const fn contains(self, other: Attributes) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if any attribute in `other` is set.
+    pub const fn intersects(self, other: Attributes) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Returns a copy without the attributes in `other`.
+    pub const fn difference(self, other: Attributes) -> Self {
+        Attributes(self.0 & !other.0)
+    }
+}
+
+impl std::ops::BitOr for Attributes {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Attributes(self.0 | other.0)
+    }
+}
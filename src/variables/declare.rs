@@ -0,0 +1,236 @@
+//! Define shell variables with attributes, like `declare`.
+
+use super::{Attributes, VariableError};
+use crate::arith;
+use crate::ffi::variables as ffi;
+use std::ffi::CString;
+use std::ptr::NonNull;
+
+/// Returns a builder to set the value and the attributes of the shell variable
+/// `name`, like the `declare` builtin.
+///
+/// The changes are applied when [`Declare::apply`] is called. If the shell
+/// variable does not exist, it is created with an empty value.
+///
+/// # Example
+///
+/// The following code is equivalent to `declare -i -x COUNT='10 * 2'`:
+///
+/// ```no_run
+/// use bash_builtins::variables;
+///
+/// # fn f() -> Result<(), variables::VariableError> {
+/// variables::declare("COUNT")
+///     .integer()
+///     .export()
+///     .value("10 * 2")
+///     .apply()?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn declare(name: &str) -> Declare<'_> {
+    Declare {
+        name,
+        value: None,
+        attributes: Attributes::empty(),
+    }
+}
+
+/// Builder to define a shell variable, created by [`declare`].
+#[must_use = "changes are applied with the `apply` method"]
+#[derive(Debug)]
+pub struct Declare<'a> {
+    name: &'a str,
+    value: Option<Vec<u8>>,
+    attributes: Attributes,
+}
+
+impl Declare<'_> {
+    /// Value to assign to the shell variable.
+    ///
+    /// `value` is not required to be valid UTF-8, but it can't contain any nul
+    /// byte.
+    pub fn value<T: AsRef<[u8]>>(mut self, value: T) -> Self {
+        self.value = Some(value.as_ref().to_owned());
+        self
+    }
+
+    /// Make the shell variable readonly, like `declare -r`.
+    ///
+    /// The value is assigned before the attribute is set.
+    pub fn readonly(self) -> Self {
+        self.with(Attributes::READONLY)
+    }
+
+    /// Export the shell variable to child processes, like `declare -x`.
+    pub fn export(self) -> Self {
+        self.with(Attributes::EXPORTED)
+    }
+
+    /// Evaluate assignments as arithmetic expressions, like `declare -i`.
+    ///
+    /// If the expression is not valid, bash prints an error, and
+    /// [`apply`](Declare::apply) returns [`VariableError::InvalidValue`].
+    pub fn integer(self) -> Self {
+        self.with(Attributes::INTEGER)
+    }
+
+    /// Convert assigned values to lowercase, like `declare -l`.
+    pub fn lowercase(mut self) -> Self {
+        self.attributes = self.attributes.difference(Attributes::UPPERCASE);
+        self.with(Attributes::LOWERCASE)
+    }
+
+    /// Convert assigned values to uppercase, like `declare -u`.
+    pub fn uppercase(mut self) -> Self {
+        self.attributes = self.attributes.difference(Attributes::LOWERCASE);
+        self.with(Attributes::UPPERCASE)
+    }
+
+    /// Make the shell variable a reference to the variable named in its
+    /// value, like `declare -n`.
+    ///
+    /// The attributes and the value are set in the reference itself, instead
+    /// of the referenced variable.
+    pub fn nameref(self) -> Self {
+        self.with(Attributes::NAMEREF)
+    }
+
+    fn with(mut self, attributes: Attributes) -> Self {
+        self.attributes = self.attributes | attributes;
+        self
+    }
+
+    /// Apply the changes to the shell variable.
+    ///
+    /// # Errors
+    ///
+    /// * [`VariableError::Readonly`] if the shell variable is readonly, and
+    ///   the builder has a value, or any attribute other than `readonly` and
+    ///   `export`.
+    /// * [`VariableError::InvalidValue`] if the value is not valid. For
+    ///   `nameref`, the value must be the name of another shell variable,
+    ///   and the shell variable can't be an array.
+    pub fn apply(self) -> Result<(), VariableError> {
+        let name = CString::new(self.name).map_err(|_| VariableError::InvalidName)?;
+
+        let value = match self.value {
+            Some(value) => Some(CString::new(value).map_err(|_| VariableError::InvalidValue)?),
+            None => None,
+        };
+
+        let attributes = self.attributes;
+        let is_nameref = attributes.contains(Attributes::NAMEREF);
+
+        unsafe {
+            if ffi::legal_identifier(name.as_ptr()) == 0 {
+                return Err(VariableError::InvalidName);
+            }
+
+            // A nameref is modified itself, instead of the shell variable
+            // referenced by it.
+            let current = if is_nameref {
+                ffi::find_variable_noref(name.as_ptr())
+            } else {
+                ffi::find_variable(name.as_ptr())
+            };
+
+            if let Some(current) = current.as_ref() {
+                let current = Attributes::from_bits(current.attributes);
+
+                let changes = attributes.difference(Attributes::READONLY | Attributes::EXPORTED);
+                if current.intersects(Attributes::READONLY | Attributes::NOASSIGN)
+                    && (value.is_some() || !changes.is_empty())
+                {
+                    return Err(VariableError::Readonly);
+                }
+
+                if is_nameref && current.intersects(Attributes::ARRAY | Attributes::ASSOC) {
+                    return Err(VariableError::InvalidValue);
+                }
+            }
+
+            if let (true, Some(value)) = (is_nameref, &value) {
+                if ffi::valid_nameref_value(value.as_ptr(), 1) == 0 || *value == name {
+                    return Err(VariableError::InvalidValue);
+                }
+            }
+
+            // Integer values are evaluated before any change, so the variable
+            // is not modified if the expression is not valid.
+            let is_integer = !is_nameref
+                && (attributes.contains(Attributes::INTEGER)
+                    || current.as_ref().is_some_and(|var| {
+                        Attributes::from_bits(var.attributes).contains(Attributes::INTEGER)
+                    }));
+
+            let value = match value {
+                Some(value) if is_integer => {
+                    let number = arith::evaluate(&value).ok_or(VariableError::InvalidValue)?;
+                    Some(CString::new(number.to_string()).unwrap())
+                }
+
+                value => value,
+            };
+
+            let (mut shell_var, created) = match NonNull::new(current) {
+                Some(shell_var) => (shell_var, false),
+                None => {
+                    let shell_var = ffi::bind_variable(name.as_ptr(), b"\0".as_ptr().cast(), 0);
+                    let shell_var = NonNull::new(shell_var).ok_or(VariableError::InvalidValue)?;
+                    (shell_var, true)
+                }
+            };
+
+            // `readonly` and `nameref` are set after the assignment, so the
+            // value is not rejected, or assigned to another variable.
+            let late = Attributes::READONLY | Attributes::NAMEREF;
+
+            // Other attributes are set before the assignment, so bash applies
+            // them to the value. They are restored if the assignment fails.
+            let previous = shell_var.as_ref().attributes;
+
+            {
+                let var = shell_var.as_mut();
+
+                if attributes.intersects(Attributes::LOWERCASE | Attributes::UPPERCASE) {
+                    let case = Attributes::LOWERCASE | Attributes::UPPERCASE | Attributes::CAPCASE;
+                    var.attributes &= !case.bits();
+                }
+
+                var.attributes |= attributes.difference(late).bits();
+            }
+
+            if let Some(value) = value {
+                let result = if is_nameref {
+                    ffi::bind_variable_value(shell_var.as_ptr(), value.as_ptr(), 0)
+                } else {
+                    ffi::bind_variable(name.as_ptr(), value.as_ptr(), 0)
+                };
+
+                shell_var = match NonNull::new(result) {
+                    Some(shell_var) => shell_var,
+                    None => {
+                        if created {
+                            ffi::unbind_variable(name.as_ptr());
+                        } else {
+                            shell_var.as_mut().attributes = previous;
+                        }
+
+                        return Err(VariableError::InvalidValue);
+                    }
+                };
+
+                shell_var.as_mut().attributes &= !Attributes::INVISIBLE.bits();
+            }
+
+            shell_var.as_mut().attributes |= attributes.bits() & late.bits();
+
+            if attributes.contains(Attributes::EXPORTED) {
+                ffi::array_needs_making = 1;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,7 +1,5 @@
 //! Module for [`DynamicVariable`].
 
-#![allow(clippy::fn_address_comparisons)]
-
 use super::VariableError;
 use crate::ffi::variables as ffi;
 use std::collections::HashMap;
@@ -10,7 +8,7 @@ use std::mem::MaybeUninit;
 use std::os::raw::c_char;
 use std::sync::atomic::AtomicBool;
 use std::sync::{atomic::Ordering::SeqCst, Mutex, MutexGuard};
-use std::{mem, panic};
+use std::{mem, panic, ptr};
 
 /// The `DynamicVariable` provides the implementation to create dynamic
 /// variables.
@@ -162,7 +160,7 @@ fn global_state() -> MutexGuard<'static, State> {
         }
     }
 
-    match unsafe { (*STATE.as_ptr()).lock() } {
+    match unsafe { (*ptr::addr_of!(STATE).cast::<Mutex<State>>()).lock() } {
         Ok(l) => l,
         Err(e) => e.into_inner(),
     }
@@ -178,7 +176,9 @@ extern "C" fn remove_all_dynvars() {
     for (varname, _) in state {
         unsafe {
             let shell_var = ffi::find_variable(varname.as_ptr());
-            if !shell_var.is_null() && (*shell_var).dynamic_value == read_var {
+            if !shell_var.is_null()
+                && (*shell_var).dynamic_value as *const () == read_var as *const ()
+            {
                 ffi::unbind_variable(varname.as_ptr());
             }
         }
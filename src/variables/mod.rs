@@ -7,12 +7,21 @@
 //! and both [`find`] and [`find_as_string`] provides a safe interface to such
 //! value.
 //!
+//! Use [`get`] and [`get_or`] to convert the value of a shell variable to any
+//! type implementing [`FromWordPointer`], like the arguments of the options.
+//!
 //! Use [`array_set`] and [`array_get`] to access the elements in an indexed
 //! array.
 //!
 //! Use [`assoc_get`] and [`assoc_get`] to access the elements in an associative
 //! array.
 //!
+//! Use [`set_array`], [`append_array`], and [`set_assoc`] to write all the
+//! elements of an array at once.
+//!
+//! Use [`declare`] to set the [attributes](Attributes) of a shell variable,
+//! like `readonly` or `export`, and [`find_attributes`] to read them.
+//!
 //! ## Example
 //!
 //! The following example uses the shell variable `$SOMENAME_LIMIT` to set the
@@ -27,11 +36,7 @@
 //! const LIMIT_VAR_NAME: &str = "SOMENAME_LIMIT";
 //!
 //! fn get_limit() -> usize {
-//!     variables::find_as_string(LIMIT_VAR_NAME)
-//!         .as_ref()
-//!         .and_then(|v| v.to_str().ok())
-//!         .and_then(|v| v.parse().ok())
-//!         .unwrap_or(DEFAULT_LIMIT)
+//!     variables::get(LIMIT_VAR_NAME).unwrap_or(DEFAULT_LIMIT)
 //! }
 //! ```
 //!
@@ -43,6 +48,7 @@
 //! Use [`bind`] to create a dynamic variable with any type implementing
 //! [`DynamicVariable`].
 
+use crate::convert::FromWordPointer;
 use crate::ffi::variables as ffi;
 use std::collections::HashMap;
 use std::ffi::{CStr, CString};
@@ -56,10 +62,14 @@ use std::os::unix::process::ExitStatusExt;
 
 mod arrays;
 mod assoc;
+mod attributes;
+mod declare;
 mod dynvars;
 
-pub use arrays::{array_get, array_set};
-pub use assoc::{assoc_get, assoc_set};
+pub use arrays::{append_array, array_get, array_set, set_array};
+pub use assoc::{assoc_get, assoc_set, set_assoc};
+pub use attributes::Attributes;
+pub use declare::{declare, Declare};
 pub use dynvars::DynamicVariable;
 
 /// Returns a string with the value of the shell variable `name`.
@@ -86,6 +96,79 @@ pub fn find_as_string(name: &str) -> Option<CString> {
     unsafe { find_raw(name).and_then(|var| var.as_str().map(|cstr| cstr.to_owned())) }
 }
 
+/// Returns the value of the shell variable `name`, converted to `T`.
+///
+/// The value is converted with the same [`FromWordPointer`] implementations
+/// used for the arguments of the options, so any type accepted in an option
+/// (like integers, [`PathBuf`](std::path::PathBuf), or the types in the
+/// [`convert`](crate::convert) module) can be used here.
+///
+/// # Errors
+///
+/// * [`VariableError::Unset`] if the shell variable does not exist, or it has
+///   no value.
+/// * [`VariableError::IsArray`] if the shell variable contains an indexed or
+///   an associative array.
+/// * [`VariableError::Conversion`] if the value can't be converted to `T`.
+///
+/// # Example
+///
+/// ```no_run
+/// use bash_builtins::variables::{self, VariableError};
+///
+/// match variables::get::<u16>("PORT") {
+///     Ok(port) => { /* … */ }
+///     Err(VariableError::Unset) => { /* … */ }
+///     Err(e) => { /* … */ }
+/// }
+/// ```
+pub fn get<T>(name: &str) -> Result<T, VariableError>
+where
+    T: for<'a> FromWordPointer<'a>,
+{
+    let var = find_raw(name).ok_or(VariableError::Unset)?;
+
+    unsafe {
+        if var.is_array() || var.is_assoc() {
+            return Err(VariableError::IsArray);
+        }
+
+        let var = var.0.as_ref();
+        if var.value.is_null() || var.attributes & ffi::ATT_INVISIBLE != 0 {
+            return Err(VariableError::Unset);
+        }
+
+        T::from_cstr(CStr::from_ptr(var.value))
+            .map_err(|e| VariableError::Conversion(e.to_string()))
+    }
+}
+
+/// Like [`get`], but returns `default` if the shell variable is unset.
+///
+/// Errors from arrays or invalid values are still returned.
+///
+/// # Example
+///
+/// ```no_run
+/// use bash_builtins::variables;
+/// use std::time::Duration;
+///
+/// # fn f() -> bash_builtins::Result<()> {
+/// let timeout = variables::get_or("SOMENAME_TIMEOUT", Duration::from_secs(5))?;
+/// # let _ = timeout;
+/// # Ok(())
+/// # }
+/// ```
+pub fn get_or<T>(name: &str, default: T) -> Result<T, VariableError>
+where
+    T: for<'a> FromWordPointer<'a>,
+{
+    match get(name) {
+        Err(VariableError::Unset) => Ok(default),
+        result => result,
+    }
+}
+
 /// Returns a copy of the value of the shell variable referenced by `name`.
 ///
 /// If the shell variable does not exist, it returns `None`.
@@ -95,6 +178,23 @@ pub fn find(name: &str) -> Option<Variable> {
     unsafe { find_raw(name).map(|var| var.get()) }
 }
 
+/// Returns the attributes of the shell variable referenced by `name`.
+///
+/// If the shell variable does not exist, it returns `None`.
+///
+/// # Example
+///
+/// ```no_run
+/// use bash_builtins::variables::{self, Attributes};
+///
+/// let readonly = variables::find_attributes("VAR_NAME")
+///     .is_some_and(|attrs| attrs.contains(Attributes::READONLY));
+/// # let _ = readonly;
+/// ```
+pub fn find_attributes(name: &str) -> Option<Attributes> {
+    unsafe { find_raw(name).map(|var| var.attributes()) }
+}
+
 /// Returns a reference to the address of the shell variable referenced by
 /// `name`.
 ///
@@ -111,6 +211,8 @@ pub fn find_raw(name: &str) -> Option<RawVariable> {
 ///
 /// `value` is not required to be valid UTF-8, but it can't contain any nul
 /// byte.
+///
+/// If the shell variable is readonly, it returns [`VariableError::Readonly`].
 pub fn set<T>(name: &str, value: T) -> Result<(), VariableError>
 where
     T: AsRef<[u8]>,
@@ -123,6 +225,7 @@ where
             return Err(VariableError::InvalidName);
         }
 
+        let value = assignment_value(ffi::find_variable(name.as_ptr()), value)?;
         ffi::bind_variable(name.as_ptr(), value.as_ptr(), 0)
     };
 
@@ -133,6 +236,38 @@ where
     }
 }
 
+/// Checks that a value can be assigned to `shell_var`, and returns the value
+/// to send to bash.
+///
+/// Bash reports readonly variables with `report_error`, which exits the shell
+/// if `set -e` is enabled, so they are rejected here.
+///
+/// If the shell variable has the `integer` attribute, the value is evaluated
+/// here, and the result is sent to bash as a plain number. Bash would evaluate
+/// the expression in the assignment, but an invalid expression (or an unset
+/// variable with `set -u`) aborts the command with a `longjmp`, skipping the
+/// Rust frames.
+pub(crate) unsafe fn assignment_value(
+    shell_var: *const ffi::ShellVar,
+    value: CString,
+) -> Result<CString, VariableError> {
+    let attributes = match shell_var.as_ref() {
+        Some(var) => Attributes::from_bits(var.attributes),
+        None => return Ok(value),
+    };
+
+    if attributes.intersects(Attributes::READONLY | Attributes::NOASSIGN) {
+        return Err(VariableError::Readonly);
+    }
+
+    if attributes.contains(Attributes::INTEGER) {
+        let number = crate::arith::evaluate(&value).ok_or(VariableError::InvalidValue)?;
+        return Ok(CString::new(number.to_string()).unwrap());
+    }
+
+    Ok(value)
+}
+
 /// Unset the shell variable referenced by `name`.
 ///
 /// Returns `true` if the shell variable is removed.
@@ -162,13 +297,30 @@ pub fn get_last_exit_status() -> ExitStatus {
     ExitStatus::from_raw(raw_exit_code)
 }
 
-/// An error from a shell variable operation, like [`set`] or [`bind`].
+/// An error from a shell variable operation, like [`set`], [`get`], or
+/// [`bind`].
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum VariableError {
     InvalidName,
     InvalidValue,
     NotAssocArray,
+    NotIndexedArray,
+    InvalidIndex,
     InternalError(&'static str),
+
+    /// The shell variable is readonly.
+    Readonly,
+
+    /// The shell variable does not exist.
+    Unset,
+
+    /// The shell variable contains an array, but a single value was expected.
+    IsArray,
+
+    /// The value of the shell variable can't be converted to the requested
+    /// type. It contains the message of the conversion error.
+    Conversion(String),
 }
 
 impl fmt::Display for VariableError {
@@ -177,7 +329,13 @@ impl fmt::Display for VariableError {
             VariableError::InvalidName => fmt.write_str("invalid variable name"),
             VariableError::InvalidValue => fmt.write_str("invalid variable value"),
             VariableError::NotAssocArray => fmt.write_str("variable is not an associative array"),
+            VariableError::NotIndexedArray => fmt.write_str("variable is not an indexed array"),
+            VariableError::InvalidIndex => fmt.write_str("bad array subscript"),
             VariableError::InternalError(cause) => write!(fmt, "internal error: {}", cause),
+            VariableError::Readonly => fmt.write_str("readonly variable"),
+            VariableError::Unset => fmt.write_str("variable is not set"),
+            VariableError::IsArray => fmt.write_str("variable is an array"),
+            VariableError::Conversion(e) => write!(fmt, "invalid value: {}", e),
         }
     }
 }
@@ -269,6 +427,16 @@ impl RawVariable {
         self.0.as_ref().attributes & ffi::ATT_ASSOC != 0
     }
 
+    /// Returns the attributes of the shell variable.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe because it does not check that the address of the
+    /// shell variable is still valid.
+    pub unsafe fn attributes(&self) -> Attributes {
+        Attributes::from_bits(self.0.as_ref().attributes)
+    }
+
     /// Extracts the contents of the shell variable, and returns a copy of the it.
     ///
     /// # Safety
@@ -0,0 +1,144 @@
+//! Arguments with the flags set by bash.
+
+use crate::bit_flags::bit_flags;
+use crate::ffi;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::c_int;
+use std::str::Utf8Error;
+
+/// An argument of the builtin, with the flags set by bash when the word was
+/// parsed and expanded.
+///
+/// Items of the [`Args::words`](crate::Args::words) iterator.
+#[derive(Clone, Copy)]
+pub struct Word<'a> {
+    text: &'a CStr,
+    flags: WordFlags,
+}
+
+impl<'a> Word<'a> {
+    pub(crate) unsafe fn from_desc(desc: &'a ffi::WordDesc) -> Self {
+        Word {
+            text: CStr::from_ptr(desc.word),
+            flags: WordFlags(desc.flags),
+        }
+    }
+
+    /// Contents of the word.
+    pub fn as_cstr(&self) -> &'a CStr {
+        self.text
+    }
+
+    /// Contents of the word, if it is valid UTF-8.
+    pub fn to_str(&self) -> Result<&'a str, Utf8Error> {
+        self.text.to_str()
+    }
+
+    /// Flags of the word.
+    pub fn flags(&self) -> WordFlags {
+        self.flags
+    }
+
+    /// Returns `true` if the word had quotes, like `"…"` or `'…'`.
+    ///
+    /// Characters escaped with `\` don't set this flag.
+    pub fn is_quoted(&self) -> bool {
+        self.flags.contains(WordFlags::QUOTED)
+    }
+
+    /// Returns `true` if the word is a variable assignment (`name=value`).
+    ///
+    /// The flag is set only if the name of the variable is not quoted, so
+    /// `'name=value'` is not an assignment.
+    pub fn is_assignment(&self) -> bool {
+        self.flags.contains(WordFlags::ASSIGNMENT)
+    }
+
+    /// Returns `true` if the word is a compound assignment, like `a=(1 2)`.
+    pub fn is_compound_assignment(&self) -> bool {
+        self.flags.contains(WordFlags::COMPOUND_ASSIGNMENT)
+    }
+}
+
+impl fmt::Debug for Word<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Word")
+            .field("text", &self.text)
+            .field("flags", &self.flags)
+            .finish()
+    }
+}
+
+/// Flags of a [`Word`].
+///
+/// The values are the `W_*` constants defined in the `shell.h` file of bash.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WordFlags(c_int);
+
+bit_flags! {
+    WordFlags {
+        /// `W_HASDOLLAR`: a `$` was present.
+        HAS_DOLLAR = 1 << 0;
+
+        /// `W_QUOTED`: some form of quoting was present.
+        QUOTED = 1 << 1;
+
+        /// `W_ASSIGNMENT`: the word is a variable assignment.
+        ASSIGNMENT = 1 << 2;
+
+        /// `W_NOSPLIT`: the word was not split.
+        NO_SPLIT = 1 << 4;
+
+        /// `W_NOGLOB`: pathname expansion was not performed.
+        NO_GLOB = 1 << 5;
+
+        /// `W_TILDEEXP`: tilde expansion was performed like in an assignment.
+        TILDE_EXPANSION = 1 << 7;
+
+        /// `W_COMPASSIGN`: the word is a compound assignment.
+        COMPOUND_ASSIGNMENT = 1 << 15;
+
+        /// `W_ASSIGNARG`: the word is an assignment argument to a builtin
+        /// like `declare`.
+        ASSIGNMENT_ARGUMENT = 1 << 17;
+
+        /// `W_HASQUOTEDNULL`: the word contains a quoted null character.
+        HAS_QUOTED_NULL = 1 << 18;
+
+        /// `W_SAWQUOTEDNULL`: the word contained a quoted null that was removed.
+        SAW_QUOTED_NULL = 1 << 21;
+
+        /// `W_ASSIGNASSOC`: the word looks like an associative array assignment.
+        ASSOC_ASSIGNMENT = 1 << 22;
+
+        /// `W_ASSIGNARRAY`: the word looks like an indexed array assignment.
+        ARRAY_ASSIGNMENT = 1 << 23;
+    }
+}
+
+impl WordFlags {
+    /// Creates an instance from the value of the `flags` field in a
+    /// `WORD_DESC` struct.
+    pub const fn from_bits(bits: c_int) -> Self {
+        WordFlags(bits)
+    }
+
+    /// Raw value of the flags.
+    pub const fn bits(self) -> c_int {
+        self.0
+    }
+
+    /// Returns `true` if all flags in `other` are set.
+    pub const fn contains(self, other: WordFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for WordFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        WordFlags(self.0 | other.0)
+    }
+}
@@ -74,14 +74,25 @@ fn create_runner_file(target: &Path) -> PathBuf {
     }
 
     w!("exec 2>&1");
+
+    // `load_example NAME [BUILTIN...]` loads the builtins from the library of
+    // the example. If no builtin is given, it loads the one named like the
+    // example.
     w!("load_example() {{");
-    w!("\tcase \"$1\" in");
+    w!("\tlocal name=\"$1\"");
+    w!("\tshift");
+    w!("\tcase \"$name\" in");
 
     for (name, path) in build_examples() {
-        w!("\t\t{}) enable -f '{}' {} ;;", name, path.display(), name);
+        w!(
+            "\t\t{}) enable -f '{}' \"${{@:-{}}}\" ;;",
+            name,
+            path.display(),
+            name
+        );
     }
 
-    w!("\t\t*) echo \"missing $1 example\"; return 1 ;;");
+    w!("\t\t*) echo \"missing $name example\"; return 1 ;;");
     w!("\tesac");
     w!("}}");
 
@@ -130,6 +141,7 @@ fn check_examples() {
         let bash = Command::new("bash")
             .env("LC_ALL", "C")
             .env("MALLOC_CHECK_", "2")
+            .env_remove("RUST_BACKTRACE")
             .env(TEST_FILE_VAR, &path)
             .stdin(Stdio::null())
             .stdout(Stdio::piped())
@@ -0,0 +1,20 @@
+#!/bin/bash
+
+load_example calc
+
+x=10
+calc 1+2 'x * 2' "$((x * 3))"
+calc 'y = x + 5'
+echo "y = $y"
+
+calc -l 'x - 8' 1 2 3
+calc -l 'x * 100' 1 || echo failed
+calc -l '1 +' 1 || echo failed
+calc '2 * (3' || echo failed
+calc '$x' || echo failed
+
+# Unset variables are 0 with `set -u`.
+set -u
+calc 'nope + 1'
+calc 1
+set +u
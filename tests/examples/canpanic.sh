@@ -9,3 +9,7 @@ enable -d canpanic
 load_example canpanic
 echo after
 canpanic
+
+BASH_BUILTINS_BACKTRACE=1
+canpanic panic 2>&1 | grep -c '^stack backtrace:$'
+unset BASH_BUILTINS_BACKTRACE
@@ -0,0 +1,13 @@
+#!/bin/bash
+
+load_example choices
+
+choices
+choices -f json
+choices --format=plain tsv tab text
+
+choices -f x || echo failed
+choices --format= || echo failed
+choices json other || echo failed
+
+help choices
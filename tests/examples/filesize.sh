@@ -0,0 +1,13 @@
+#!/bin/bash
+
+load_example filesize
+
+cd "$(mktemp -d)"
+head -c 3072 /dev/zero > file
+
+filesize file
+filesize -k file
+filesize -k -m file || echo failed
+filesize -mk file || echo failed
+
+rm file
@@ -0,0 +1,23 @@
+#!/bin/bash
+
+load_example kv
+
+kv set a 1
+kv set b 2
+kv get a
+kv get -d none c
+kv get c || echo failed
+kv list
+
+kv || echo failed
+kv remove a || echo failed
+kv get -x a || echo failed
+kv set a || echo failed
+kv list a || echo failed
+kv clear -v a || echo failed
+
+kv get --help || echo failed
+help kv
+
+kv clear -v
+kv list
@@ -0,0 +1,14 @@
+#!/bin/bash
+
+load_example loadwarn
+loadwarn
+enable -d loadwarn
+
+LOADWARN_CONFIG=x
+load_example loadwarn
+loadwarn
+enable -d loadwarn
+
+LOADWARN_FAIL=1
+load_example loadwarn
+loadwarn || echo "exit code: $?"
@@ -0,0 +1,15 @@
+#!/bin/bash
+
+load_example longoptions
+
+longoptions
+longoptions -v --verbose -l 1 --limit 2 --limit=3
+longoptions --name first --name=second -vl4 a b
+longoptions --color --color=never -- --limit
+
+longoptions --limit || echo failed
+longoptions --verbose=yes || echo failed
+longoptions --limit=x || echo failed
+longoptions --unknown || echo failed
+longoptions --help || echo failed
+longoptions --help=foo || echo failed
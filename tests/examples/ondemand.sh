@@ -0,0 +1,15 @@
+#!/bin/bash
+
+ONDEMAND_FAIL=1
+load_example ondemand
+echo loaded
+
+ondemand || echo failed
+
+unset ONDEMAND_FAIL
+ondemand
+ondemand
+
+enable -d ondemand
+load_example ondemand
+ondemand
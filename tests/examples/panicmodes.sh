@@ -0,0 +1,13 @@
+#!/bin/bash
+
+load_example panicmodes panicreset panickeep
+
+panicreset
+panicreset
+panicreset panic || echo "exit code: $?"
+panicreset
+
+panickeep
+panickeep
+panickeep panic || echo "exit code: $?"
+panickeep
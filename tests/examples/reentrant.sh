@@ -0,0 +1,41 @@
+#!/bin/bash
+
+load_example reentrant nonreentrant reentrant
+
+a=(10 20 30)
+
+reentrant '1 + 1'
+reentrant 'a[$(reentrant 2)]'
+
+nonreentrant '1 + 1'
+nonreentrant 'a[$(nonreentrant 2)]' || echo failed
+
+# Interrupted calls. The jump aborts the test file, so they are tested in new
+# shells. The EXIT trap invokes the builtin again, after the jump.
+
+load_interrupted="$(declare -f load_example); load_example reentrant interrupted"
+
+{
+    echo "$load_interrupted"
+    echo 'interrupted -j; echo "not printed"'
+    echo 'interrupted'
+    echo 'interrupted -j'
+    echo 'true'
+} | bash
+
+bash -c "$load_interrupted"'
+    trap "interrupted; true" EXIT
+    interrupted -j
+    echo "not printed"
+'
+
+source_file="$(mktemp)"
+printf 'interrupted -j\necho "not printed"\n' > "$source_file"
+
+bash -c "$load_interrupted"'
+    trap "interrupted; true" EXIT
+    source "$1"
+    echo "not printed"
+' - "$source_file"
+
+rm -f "$source_file"
@@ -0,0 +1,54 @@
+#!/bin/bash
+
+set -euo pipefail
+
+load_example setarray
+
+# Indexed arrays.
+
+setarray RED a b c
+declare -p RED
+
+RED[10]=x
+setarray RED d e
+declare -p RED
+
+setarray -a RED f g
+declare -p RED
+
+setarray RED
+declare -p RED
+
+# Strings are converted to arrays.
+
+BLUE=first
+setarray -a BLUE second
+declare -p BLUE
+
+# Associative arrays.
+
+setarray -A GREEN a=1 b=2
+declare -p GREEN
+
+setarray -A GREEN c=3
+declare -p GREEN
+
+# Errors.
+
+setarray GREEN x || echo "exit = $?"
+setarray -A RED x=1 || echo "exit = $?"
+
+declare -r YELLOW=1
+setarray YELLOW x || echo "exit = $?"
+declare -p YELLOW
+
+# Local variables.
+
+f() {
+    local -a PURPLE=(z)
+    setarray PURPLE a b
+    declare -p PURPLE
+}
+
+f
+declare -p PURPLE 2>&1 || :
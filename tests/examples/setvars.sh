@@ -0,0 +1,33 @@
+#!/bin/bash
+
+load_example setvars
+
+HOME=/home/user
+x="a b"
+
+setvars a=1 b=$x c=~/bin
+declare -p a b c
+
+setvars a+=2 b+=" c"
+declare -p a b
+
+setvars arr[0]=x arr[1+1]=y arr[2]+=z arr[-1]+=w
+declare -p arr
+
+declare -A map=([k]=v)
+setvars map[k]+=1 'map[other key]=2'
+declare -p map
+
+setvars arr+=0
+declare -p arr
+
+setvars unused
+setvars 1x=1 || echo failed
+setvars a-b=1 || echo failed
+setvars 'a[1=1' || echo failed
+setvars arr[-10]=1 || echo failed
+
+declare -i n=5
+declare -ai narr=(1 2)
+setvars n+=1 narr[1]+='2 * 3' narr[2]+=4
+declare -p n narr
@@ -0,0 +1,33 @@
+#!/bin/bash
+
+load_example shelltypes
+
+shelltypes -s INT
+shelltypes -s sigterm
+shelltypes -s 9
+shelltypes -s EXIT
+shelltypes -s NOPE || echo failed
+
+shelltypes -u 1
+shelltypes -u 200
+shelltypes -u -1 || echo failed
+shelltypes -u x || echo failed
+
+shelltypes -m 755
+shelltypes -m u+x,go-r
+shelltypes -m a=rX
+shelltypes -m 9 || echo failed
+shelltypes -m u%x || echo failed
+shelltypes -m u+q || echo failed
+
+shelltypes -o root -g root
+shelltypes -o 1234 -g 5678
+shelltypes -o no-such-user || echo failed
+shelltypes -g no-such-group || echo failed
+
+shelltypes -t 1.5s -b 10M
+shelltypes -t 250ms -b 4KiB
+shelltypes -t 3 -b 4KB
+shelltypes -t 3w || echo failed
+shelltypes -b 1.2.3 || echo failed
+shelltypes -b 64E || echo failed
@@ -0,0 +1,13 @@
+#!/bin/bash
+
+load_example special
+
+enable -s | grep -w special
+
+A=1 special
+echo "A=${A-unset}"
+
+set -o posix
+
+B=2 special
+echo "B=${B-unset}"
@@ -0,0 +1,20 @@
+#!/bin/bash
+
+load_example stack stack push pop
+
+push a b c
+stack
+pop
+pop
+stack
+
+# The instance is kept until the last name is removed.
+enable -d push pop
+stack
+enable -d stack
+
+load_example stack push pop
+pop || echo "empty stack"
+push x
+pop
+enable -d push pop
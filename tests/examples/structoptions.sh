@@ -0,0 +1,18 @@
+#!/bin/bash
+
+load_example structoptions
+
+structoptions -l 1
+structoptions -qvvv -l 2 -v -n first -n second
+structoptions --limit=3 -i a --include b -i c x y
+
+structoptions || echo failed
+structoptions -l x || echo failed
+structoptions -l 1 -X || echo failed
+help structoptions
+
+structoptions -l 1 -x -n name
+structoptions -l 1 -x || echo failed
+
+structoptions -l 1 +x a +b
+structoptions -l 1 +q || echo failed
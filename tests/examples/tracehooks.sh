@@ -0,0 +1,15 @@
+#!/bin/bash
+
+load_example tracehooks
+tracehooks
+(tracehooks; tracehooks)
+tracehooks
+enable -d tracehooks
+
+load_example tracehooks
+TRACEHOOKS_BUSY=1
+enable -d tracehooks
+unset TRACEHOOKS_BUSY
+
+load_example tracehooks
+tracehooks
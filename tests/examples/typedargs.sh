@@ -0,0 +1,12 @@
+#!/bin/bash
+
+load_example typedargs
+
+typedargs /tmp
+typedargs -v /tmp 1 2 3
+typedargs -- -v 10
+
+typedargs || echo failed
+typedargs /tmp 1 x || echo failed
+
+help typedargs
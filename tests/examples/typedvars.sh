@@ -0,0 +1,15 @@
+#!/bin/bash
+
+set -euo pipefail
+
+load_example typedvars
+
+NUM=123
+TEXT=abc
+ARRAY=(1 2 3)
+declare -A ASSOC=([a]=1)
+declare EMPTY
+
+typedvars NUM TEXT ARRAY ASSOC EMPTY MISSING
+echo .
+typedvars -d 10 NUM TEXT ARRAY EMPTY MISSING
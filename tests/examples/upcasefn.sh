@@ -0,0 +1,14 @@
+#!/bin/bash
+
+set -euo pipefail
+
+load_example upcasefn
+
+echo .
+upcasefn a bb1 äλ
+echo .
+upcasefn -v a bb1
+echo .
+upcasefn -x a || echo "exit = $?"
+echo .
+help upcasefn
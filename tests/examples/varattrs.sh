@@ -0,0 +1,63 @@
+#!/bin/bash
+
+set -euo pipefail
+
+load_example varattrs
+
+# Integer variables.
+
+varattrs -i COUNT '10 * 2'
+declare -p COUNT
+
+COUNT+=1
+declare -p COUNT
+
+varattrs COUNT 'COUNT + 5'
+declare -p COUNT
+
+varattrs COUNT '1 +' 2>&1 || echo "exit = $?"
+declare -p COUNT
+
+# Case conversion.
+
+varattrs -u NAME abc
+declare -p NAME
+
+varattrs -l NAME XyZ
+declare -p NAME
+varattrs -p NAME
+
+# Variables are not modified if the value is not valid.
+
+varattrs -u -i NAME '1 +' 2>&1 || echo "exit = $?"
+declare -p NAME
+
+varattrs -i NEWVAR '1 +' 2>&1 || echo "exit = $?"
+varattrs -p NEWVAR
+
+# Exported variables.
+
+varattrs -x EXPVAR value
+env | grep ^EXPVAR=
+
+# Namerefs.
+
+TARGET=1
+varattrs -n REF TARGET
+REF=2
+declare -p REF TARGET
+
+varattrs -n SELF SELF || echo "exit = $?"
+varattrs -n BADREF '1 2' || echo "exit = $?"
+
+# Readonly variables.
+
+varattrs -r -x RO first
+declare -p RO
+
+varattrs RO second || echo "exit = $?"
+varattrs -i RO || echo "exit = $?"
+varattrs -r -x RO
+declare -p RO
+
+varattrs -p MISSING
@@ -0,0 +1,8 @@
+#!/bin/bash
+
+load_example words
+
+x=1
+words plain "quoted" 'single' a\ b $x "$x" name=value 'name=value' "a[1]=2" ''
+words *.none
+words a[1]=2 -- x=$x
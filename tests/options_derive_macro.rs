@@ -7,6 +7,7 @@ use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
 #[cfg(unix)]
 #[test]
 fn parse_options() {
+    #[allow(dead_code)]
     #[derive(BuiltinOptions, Debug)]
     enum Opt<'a> {
         #[opt = 'a']
@@ -51,6 +52,7 @@ fn parse_options() {
     assert_matches!(Opt::from_option(b'z' as _, None), Err(Error::Usage));
 
     assert_eq!(SH_NEEDARG_CALLS.swap(0, SeqCst), 0);
+    assert_eq!(SH_INVALIDOPT_CALLS.swap(0, SeqCst), 1);
     assert_eq!(BUILTIN_USAGE_CALLS.swap(0, SeqCst), 1);
 
     // Missing argument
@@ -79,6 +81,124 @@ fn parse_options() {
     assert_eq!(BUILTIN_USAGE_CALLS.swap(0, SeqCst), 0);
 }
 
+#[test]
+fn long_options() {
+    #[allow(dead_code)]
+    #[derive(BuiltinOptions, Debug)]
+    enum Opt<'a> {
+        #[opt(short = 'a', long = "all")]
+        All,
+
+        #[opt(long = "limit")]
+        Limit(u32),
+
+        #[opt(long = "color")]
+        Color(Option<&'a str>),
+
+        #[opt = 'n']
+        Name(String),
+    }
+
+    assert_eq!(Opt::options(), b"an:\0");
+
+    let long_options: Vec<_> = Opt::long_options()
+        .iter()
+        .map(|o| (o.name, o.opt, o.argument))
+        .collect();
+
+    assert_eq!(
+        long_options,
+        [
+            ("all", b'a' as c_int, 0),
+            ("limit", 0x101, b':'),
+            ("color", 0x102, b';'),
+        ]
+    );
+
+    assert_matches!(Opt::from_option(b'a' as _, None), Ok(Opt::All));
+
+    assert_matches!(
+        Opt::from_option(0x101, Some(CStr::from_bytes_with_nul(b"10\0").unwrap())),
+        Ok(Opt::Limit(10))
+    );
+
+    assert_matches!(Opt::from_option(0x102, None), Ok(Opt::Color(None)));
+
+    assert_eq!(SH_NEEDARG_CALLS.swap(0, SeqCst), 0);
+    assert_eq!(BUILTIN_USAGE_CALLS.swap(0, SeqCst), 0);
+}
+
+#[test]
+fn short_and_long_option_shorthand() {
+    #[derive(BuiltinOptions, Debug)]
+    enum Opt {
+        #[opt('v', long = "verbose")]
+        Verbose,
+    }
+
+    assert_eq!(Opt::options(), b"v\0");
+
+    let long_options: Vec<_> = Opt::long_options()
+        .iter()
+        .map(|o| (o.name, o.opt, o.argument))
+        .collect();
+
+    assert_eq!(long_options, [("verbose", b'v' as c_int, 0)]);
+
+    assert_matches!(Opt::from_option(b'v' as _, None), Ok(Opt::Verbose));
+}
+
+#[test]
+fn plus_options() {
+    #[allow(dead_code)]
+    #[derive(BuiltinOptions, Debug)]
+    enum Opt {
+        #[opt = 'x']
+        Export,
+
+        #[opt(short = 'x', plus)]
+        NoExport,
+
+        #[opt(short = 'i', plus)]
+        Indent(u8),
+
+        #[opt = 'i']
+        NoIndent(u8),
+
+        #[opt = 'v']
+        Verbose,
+    }
+
+    assert_eq!(Opt::options(), b"+xi:v\0");
+
+    assert_matches!(Opt::from_option(b'x' as _, None), Ok(Opt::Export));
+    assert_matches!(
+        Opt::from_option(b'x' as c_int | 0x1_0000, None),
+        Ok(Opt::NoExport)
+    );
+
+    assert_matches!(
+        Opt::from_option(
+            b'i' as c_int | 0x1_0000,
+            Some(CStr::from_bytes_with_nul(b"4\0").unwrap())
+        ),
+        Ok(Opt::Indent(4))
+    );
+
+    assert_eq!(SH_INVALIDOPT_CALLS.swap(0, SeqCst), 0);
+    assert_eq!(BUILTIN_USAGE_CALLS.swap(0, SeqCst), 0);
+
+    // `+v` is not accepted.
+
+    assert_matches!(
+        Opt::from_option(b'v' as c_int | 0x1_0000, None),
+        Err(Error::Usage)
+    );
+
+    assert_eq!(SH_INVALIDOPT_CALLS.swap(0, SeqCst), 1);
+    assert_eq!(BUILTIN_USAGE_CALLS.swap(0, SeqCst), 1);
+}
+
 // Mock bash functions and static varibles required by the
 // `BuiltinOptions` trait.
 
@@ -86,6 +206,8 @@ static SH_NEEDARG_CALLS: AtomicUsize = AtomicUsize::new(0);
 
 static BUILTIN_USAGE_CALLS: AtomicUsize = AtomicUsize::new(0);
 
+static SH_INVALIDOPT_CALLS: AtomicUsize = AtomicUsize::new(0);
+
 #[no_mangle]
 extern "C" fn builtin_error(_: *const c_char) {}
 
@@ -105,8 +227,16 @@ extern "C" fn sh_needarg(_: *const c_char) {
     SH_NEEDARG_CALLS.fetch_add(1, SeqCst);
 }
 
+#[no_mangle]
+extern "C" fn sh_invalidopt(_: *const c_char) {
+    SH_INVALIDOPT_CALLS.fetch_add(1, SeqCst);
+}
+
 #[no_mangle]
 static mut list_opttype: c_int = 0;
 
 #[no_mangle]
 static mut list_optopt: c_int = 0;
+
+#[no_mangle]
+static mut this_command_name: *const c_char = std::ptr::null();
@@ -0,0 +1,12 @@
+use bash_builtins::BuiltinOptions;
+
+#[derive(BuiltinOptions)]
+struct Opt {
+    #[arg]
+    first: Option<u32>,
+
+    #[arg]
+    second: u32,
+}
+
+fn main() {}
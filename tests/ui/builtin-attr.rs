@@ -0,0 +1,14 @@
+use bash_builtins::{builtin, Result};
+
+#[builtin(name = "first", create = Default::default)]
+fn first(words: Vec<String>) -> Result<()> {
+    drop(words);
+    Ok(())
+}
+
+#[builtin(name = "second")]
+fn second(#[opt = 'n'] name: &str) {
+    drop(name);
+}
+
+fn main() {}
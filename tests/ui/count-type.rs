@@ -0,0 +1,9 @@
+use bash_builtins::BuiltinOptions;
+
+#[derive(BuiltinOptions)]
+struct Opt {
+    #[opt(short = 'v', count)]
+    verbose: bool,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use bash_builtins::BuiltinCommands;
+
+#[derive(BuiltinCommands)]
+enum Command {
+    List,
+
+    #[command = "list"]
+    ListAll,
+}
+
+fn main() {}
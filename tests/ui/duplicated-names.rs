@@ -0,0 +1,17 @@
+use bash_builtins::{builtin_metadata, Args, Builtin, Result};
+
+builtin_metadata!(
+    names = ["first", "second", "first"],
+    create = Example::default,
+);
+
+#[derive(Default)]
+struct Example;
+
+impl Builtin for Example {
+    fn call(&mut self, _args: &mut Args) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn main() {}
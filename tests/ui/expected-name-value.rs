@@ -2,7 +2,7 @@ use bash_builtins::BuiltinOptions;
 
 #[derive(BuiltinOptions)]
 enum Opt {
-    #[opt('a')]
+    #[opt("a")]
     A,
 }
 
@@ -0,0 +1,18 @@
+use bash_builtins::{builtin_metadata, Args, Builtin, Result};
+
+builtin_metadata!(
+    name = "example",
+    create = Example::default,
+    localvar = true,
+);
+
+#[derive(Default)]
+struct Example;
+
+impl Builtin for Example {
+    fn call(&mut self, _args: &mut Args) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn main() {}
@@ -0,0 +1,34 @@
+#![deny(deprecated)]
+
+use bash_builtins::{builtin_metadata, Args, Builtin, BuiltinOptions, Result};
+
+builtin_metadata!(
+    name = "example",
+    create = Example::default,
+    long_doc = "
+        Options:
+          -a\tFirst option.
+          -x, --extra\tNot accepted by the parser.
+    ",
+    options = Opt,
+);
+
+#[derive(BuiltinOptions)]
+enum Opt {
+    #[opt = 'a']
+    A,
+
+    #[opt = 'b']
+    B,
+}
+
+#[derive(Default)]
+struct Example;
+
+impl Builtin for Example {
+    fn call(&mut self, _: &mut Args) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn main() {}
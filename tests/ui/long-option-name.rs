@@ -0,0 +1,12 @@
+use bash_builtins::BuiltinOptions;
+
+#[derive(BuiltinOptions)]
+enum Opt {
+    #[opt(short = 'a', long = "all")]
+    A,
+
+    #[opt(long = "-b")]
+    B,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use bash_builtins::BuiltinOptions;
+
+#[derive(BuiltinOptions)]
+enum Opt {
+    #[opt = 'i']
+    Indent(u8),
+
+    #[opt(short = 'i', plus)]
+    NoIndent,
+}
+
+fn main() {}
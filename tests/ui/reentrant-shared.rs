@@ -0,0 +1,14 @@
+use bash_builtins::{builtin_metadata, Args, Builtin, Result};
+
+builtin_metadata!(name = "test", create = Test::default, reentrant = true);
+
+#[derive(Default)]
+struct Test;
+
+impl Builtin for Test {
+    fn call(&mut self, _: &mut Args) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn main() {}
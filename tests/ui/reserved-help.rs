@@ -0,0 +1,9 @@
+use bash_builtins::BuiltinOptions;
+
+#[derive(BuiltinOptions)]
+enum Opt {
+    #[opt(long = "help")]
+    Help,
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use bash_builtins::{builtin_metadata, Args, Builtin, Result};
+
+builtin_metadata!(
+    name = "example",
+    create = Example::default,
+    special = true,
+    posix = true,
+);
+
+#[derive(Default)]
+struct Example;
+
+impl Builtin for Example {
+    fn call(&mut self, _args: &mut Args) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn main() {}
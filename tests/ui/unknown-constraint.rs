@@ -0,0 +1,12 @@
+use bash_builtins::BuiltinOptions;
+
+#[derive(BuiltinOptions)]
+struct Opt {
+    #[opt(short = 'a', conflicts_with = 'b')]
+    all: bool,
+
+    #[opt(short = 'o', requires = "files")]
+    output: Option<String>,
+}
+
+fn main() {}